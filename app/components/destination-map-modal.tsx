@@ -35,6 +35,7 @@ type DestinationMapModalProps = {
   destination: MapDestination;
   paidRecommendations: PaidMapRecommendation[];
   residentialRecommendations: ResidentialMapRecommendation[];
+  title?: string;
 };
 
 function buildGoogleSearchUrl(lat: number, lng: number): string {
@@ -51,6 +52,7 @@ export function DestinationMapModal({
   destination,
   paidRecommendations,
   residentialRecommendations,
+  title = "Destination + Parking Map",
 }: DestinationMapModalProps) {
   const mapRef = useRef<HTMLDivElement | null>(null);
   const [mapError, setMapError] = useState<string | null>(null);
@@ -210,7 +212,7 @@ export function DestinationMapModal({
     <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/45 p-4">
       <div className="w-full max-w-3xl rounded-xl border border-black/15 bg-white p-4 text-black shadow-xl">
         <div className="mb-2 flex items-center justify-between">
-          <h4 className="text-base font-semibold">Destination + Parking Map</h4>
+          <h4 className="text-base font-semibold">{title}</h4>
           <button
             type="button"
             onClick={onClose}
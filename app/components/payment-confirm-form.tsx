@@ -4,7 +4,14 @@ import Link from "next/link";
 import { useEffect, useRef, useState } from "react";
 import type { ChangeEvent, FormEvent } from "react";
 
-import { loadStoredPaymentProfile, saveStoredPaymentProfile } from "@/lib/payment-profile-storage";
+import {
+  loadPaymentProfileSummary,
+  loadStoredPaymentProfile,
+  type PaymentProfileSummary,
+  PaymentProfileStorageError,
+  saveStoredPaymentProfile,
+} from "@/lib/payment-profile-storage";
+import { isTauriRuntime } from "@/lib/tauri-invoke";
 
 type PendingPaymentRequest = {
   sessionId: string;
@@ -91,6 +98,7 @@ export function PaymentConfirmForm() {
   const [loadingRequest, setLoadingRequest] = useState(true);
   const [loadingStoredProfile, setLoadingStoredProfile] = useState(true);
   const [hasStoredProfile, setHasStoredProfile] = useState(false);
+  const [storedCardSummary, setStoredCardSummary] = useState<PaymentProfileSummary | null>(null);
   const [details, setDetails] = useState<PaymentDetailsState>({
     cardNumber: "",
     cardCCV: "",
@@ -110,11 +118,22 @@ export function PaymentConfirmForm() {
 
     void (async () => {
       let stored = null as Awaited<ReturnType<typeof loadStoredPaymentProfile>>;
+      let summary = null as PaymentProfileSummary | null;
       try {
-        stored = await loadStoredPaymentProfile();
+        // The desktop app only exposes the masked summary; `load_payment_profile` is denied to the
+        // webview, so the full profile is only read from the browser build's local storage.
+        if (isTauriRuntime()) {
+          summary = await loadPaymentProfileSummary();
+        } else {
+          stored = await loadStoredPaymentProfile();
+        }
         setHasStoredProfile(Boolean(stored));
-      } catch {
+        setStoredCardSummary(summary);
+      } catch (error) {
         setHasStoredProfile(false);
+        if (error instanceof PaymentProfileStorageError) {
+          setErrorMessage(`Saved payment details could not be loaded: ${error.message}`);
+        }
       }
 
       let licenseFromProfile = "";
@@ -131,13 +150,13 @@ export function PaymentConfirmForm() {
         licenseFromProfile = "";
       }
 
-      if (stored || licenseFromProfile) {
+      if (stored || summary || licenseFromProfile) {
         setDetails((current) => ({
           ...current,
           cardNumber: stored?.cardNumber || current.cardNumber,
-          cardExpiration: stored?.cardExpiration || current.cardExpiration,
+          cardExpiration: stored?.cardExpiration || summary?.cardExpiration || current.cardExpiration,
           zipCode: stored?.zipCode || current.zipCode,
-          license: stored?.license || current.license || licenseFromProfile,
+          license: stored?.license || summary?.plate || current.license || licenseFromProfile,
           cardCCV: "",
         }));
       }
@@ -312,6 +331,11 @@ export function PaymentConfirmForm() {
         {!loadingStoredProfile ? (
           hasStoredProfile ? (
             <p className="mt-2 text-black/70">Saved card details found on this device. Enter CCV to continue.</p>
+          ) : storedCardSummary ? (
+            <p className="mt-2 text-black/70">
+              Saved card ending in {storedCardSummary.lastFour} found on this device. Re-enter the card number and
+              CCV to continue.
+            </p>
           ) : (
             <p className="mt-2 text-black/70">No saved card details found. Enter details below to continue.</p>
           )
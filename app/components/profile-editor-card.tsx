@@ -2,6 +2,7 @@
 
 import { useRouter } from "next/navigation";
 import { useEffect, useState } from "react";
+import { wipeLocalData } from "@/lib/payment-profile-storage";
 
 type SavedProfile = {
   id?: string;
@@ -195,6 +196,7 @@ export function ProfileEditorCard() {
     void (async () => {
       try {
         await fetch("/api/account/logout", { method: "POST" });
+        await wipeLocalData();
       } finally {
         router.push("/");
       }
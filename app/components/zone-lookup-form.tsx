@@ -1,7 +1,9 @@
 "use client";
 
 import { useEffect, useMemo, useRef, useState } from "react";
-import { DestinationMapModal } from "@/app/components/destination-map-modal";
+import { DestinationMapModal, type MapDestination } from "@/app/components/destination-map-modal";
+import { tryInvokeTauri } from "@/lib/tauri-invoke";
+import { listenForTrayActions, trayActionFromSearch, type TrayAction } from "@/lib/tray-actions";
 
 type ParkingRecommendation = {
   zoneNumber: string;
@@ -184,6 +186,16 @@ type ResumeParkingSessionResponse = {
   };
 };
 
+/** Row from the desktop `list_parking_sessions` command, as far as the tray actions need it. */
+type LocalParkingSession = {
+  id: string;
+  parkedLat: number;
+  parkedLng: number;
+  capturedZoneNumber: string | null;
+  confirmedZoneNumber: string | null;
+  resumeToken: string;
+};
+
 type ParkingSessionError = {
   error?: string;
 };
@@ -272,6 +284,7 @@ export function ZoneLookupForm() {
   const [destination, setDestination] = useState("");
   const [destinationResult, setDestinationResult] = useState<DestinationLookupResponse | null>(null);
   const [isMapOpen, setIsMapOpen] = useState(false);
+  const [parkedMapLocation, setParkedMapLocation] = useState<MapDestination | null>(null);
 
   const [isTrackingLiveLocation, setIsTrackingLiveLocation] = useState(false);
   const [liveLocation, setLiveLocation] = useState<LiveCoordinates | null>(null);
@@ -296,6 +309,7 @@ export function ZoneLookupForm() {
   const lastSentLocationRef = useRef<LiveCoordinates | null>(null);
   const latestLocationRef = useRef<LiveCoordinates | null>(null);
   const loadedResumeTokenRef = useRef<string | null>(null);
+  const trayActionHandlerRef = useRef<(action: TrayAction) => void>(() => {});
 
   const [geolocationSupported, setGeolocationSupported] = useState<boolean | null>(null);
 
@@ -321,6 +335,18 @@ export function ZoneLookupForm() {
     lng: number,
     accuracyMeters: number | null,
   ): Promise<CurrentZoneResponse> => {
+    const tauriResult = await tryInvokeTauri<CurrentZoneResponse>("lookup_current_zone", {
+      lat,
+      lng,
+      accuracyMeters,
+    });
+    if (tauriResult.ok) {
+      return tauriResult.value;
+    }
+    if (tauriResult.reason === "command") {
+      throw new Error(tauriResult.error.message);
+    }
+
     const response = await fetch("/api/parking/current-zone", {
       method: "POST",
       headers: {
@@ -363,6 +389,16 @@ export function ZoneLookupForm() {
   };
 
   const loadParkingSessionFromToken = async (resumeToken: string): Promise<ResumeParkingSessionResponse> => {
+    const tauriResult = await tryInvokeTauri<ResumeParkingSessionResponse>("resume_parking_session", {
+      token: resumeToken,
+    });
+    if (tauriResult.ok) {
+      return tauriResult.value;
+    }
+    if (tauriResult.reason === "command") {
+      throw new Error(tauriResult.error.message);
+    }
+
     const response = await fetch(`/api/parking/session/resume?token=${encodeURIComponent(resumeToken)}`, {
       method: "GET",
       cache: "no-store",
@@ -444,6 +480,58 @@ export function ZoneLookupForm() {
     };
   }, []);
 
+  /** Reopens a saved session in the renew flow; `source` names where the request came from. */
+  const restoreParkingSession = async (resumeToken: string, source: string) => {
+    loadedResumeTokenRef.current = resumeToken;
+    setSessionRestoreMessage("Loading saved parking session...");
+    setLiveLocationError(null);
+
+    try {
+      const payload = await loadParkingSessionFromToken(resumeToken);
+      const session = payload.session;
+
+      const restoredCoords: LiveCoordinates = {
+        lat: session.lat,
+        lng: session.lng,
+        accuracyMeters: session.accuracyMeters,
+      };
+
+      setRenewFromSessionId(session.id);
+      setPaymentZoneValue(session.zoneNumber || "");
+      setPaymentDurationMinutes(String(session.durationMinutes || 60));
+      setLiveLocation(restoredCoords);
+      latestLocationRef.current = restoredCoords;
+      lastSentLocationRef.current = restoredCoords;
+
+      const currentZone = await resolveCurrentZoneForCoords(restoredCoords);
+      setParkedSnapshot(currentZone);
+
+      try {
+        const captured = await captureParkingSession(restoredCoords);
+        setParkingSessionId(captured.sessionId);
+        setParkingSessionResumeToken(captured.resumeToken);
+        setRulesRundown(captured.rulesRundown);
+        setPaymentZoneValue(captured.captured.zoneNumber || currentZone.zone.zoneNumber || session.zoneNumber || "");
+      } catch {
+        setParkingSessionId(session.id);
+        setParkingSessionResumeToken(resumeToken);
+        setRulesRundown(session.rulesRundown);
+      }
+
+      if (session.isExpired) {
+        setSessionRestoreMessage(`Loaded session from ${source}. This session has expired; renew to continue parking.`);
+      } else if (session.expiresAt) {
+        setSessionRestoreMessage(`Loaded session from ${source}. Current expiry: ${formatTimestamp(session.expiresAt)}.`);
+      } else {
+        setSessionRestoreMessage(`Loaded session from ${source}.`);
+      }
+    } catch (error) {
+      const message = error instanceof Error ? error.message : "Failed to load saved parking session.";
+      setLiveLocationError(message);
+      setSessionRestoreMessage(null);
+    }
+  };
+
   useEffect(() => {
     if (typeof window === "undefined") {
       return;
@@ -454,56 +542,7 @@ export function ZoneLookupForm() {
       return;
     }
 
-    loadedResumeTokenRef.current = resumeToken;
-    setSessionRestoreMessage("Loading saved parking session...");
-    setLiveLocationError(null);
-
-    void (async () => {
-      try {
-        const payload = await loadParkingSessionFromToken(resumeToken);
-        const session = payload.session;
-
-        const restoredCoords: LiveCoordinates = {
-          lat: session.lat,
-          lng: session.lng,
-          accuracyMeters: session.accuracyMeters,
-        };
-
-        setRenewFromSessionId(session.id);
-        setPaymentZoneValue(session.zoneNumber || "");
-        setPaymentDurationMinutes(String(session.durationMinutes || 60));
-        setLiveLocation(restoredCoords);
-        latestLocationRef.current = restoredCoords;
-        lastSentLocationRef.current = restoredCoords;
-
-        const currentZone = await resolveCurrentZoneForCoords(restoredCoords);
-        setParkedSnapshot(currentZone);
-
-        try {
-          const captured = await captureParkingSession(restoredCoords);
-          setParkingSessionId(captured.sessionId);
-          setParkingSessionResumeToken(captured.resumeToken);
-          setRulesRundown(captured.rulesRundown);
-          setPaymentZoneValue(captured.captured.zoneNumber || currentZone.zone.zoneNumber || session.zoneNumber || "");
-        } catch {
-          setParkingSessionId(session.id);
-          setParkingSessionResumeToken(resumeToken);
-          setRulesRundown(session.rulesRundown);
-        }
-
-        if (session.isExpired) {
-          setSessionRestoreMessage("Loaded session from SMS link. This session has expired; renew to continue parking.");
-        } else if (session.expiresAt) {
-          setSessionRestoreMessage(`Loaded session from SMS link. Current expiry: ${formatTimestamp(session.expiresAt)}.`);
-        } else {
-          setSessionRestoreMessage("Loaded session from SMS link.");
-        }
-      } catch (error) {
-        const message = error instanceof Error ? error.message : "Failed to load saved parking session.";
-        setLiveLocationError(message);
-        setSessionRestoreMessage(null);
-      }
-    })();
+    void restoreParkingSession(resumeToken, "SMS link");
   }, []);
 
   const startLiveLocationTracking = () => {
@@ -714,6 +753,89 @@ export function ZoneLookupForm() {
     }
   };
 
+  const loadLocalParkingSession = async (sessionId: string): Promise<LocalParkingSession> => {
+    const tauriResult = await tryInvokeTauri<LocalParkingSession[]>("list_parking_sessions");
+    if (!tauriResult.ok) {
+      throw new Error(
+        tauriResult.reason === "command"
+          ? tauriResult.error.message
+          : "Saved parking sessions are only available in the desktop app.",
+      );
+    }
+
+    const session = tauriResult.value.find((item) => item.id === sessionId);
+    if (!session) {
+      throw new Error("The parking session shown in the tray is no longer saved.");
+    }
+    return session;
+  };
+
+  const onTrayAction = async ({ action, sessionId }: TrayAction) => {
+    if (action === "parked") {
+      await onHaveParked();
+      return;
+    }
+    if (!sessionId) {
+      setLiveLocationError("No parking session is running.");
+      return;
+    }
+
+    try {
+      const session = await loadLocalParkingSession(sessionId);
+      if (action === "renew") {
+        await restoreParkingSession(session.resumeToken, "tray menu");
+        return;
+      }
+
+      const zoneNumber = session.confirmedZoneNumber ?? session.capturedZoneNumber;
+      setIsMapOpen(false);
+      setParkedMapLocation({
+        lat: session.parkedLat,
+        lng: session.parkedLng,
+        name: "Parked location",
+        street: zoneNumber ? `Zone ${zoneNumber}` : "",
+      });
+    } catch (error) {
+      const message = error instanceof Error ? error.message : "Failed to load parking session.";
+      setLiveLocationError(message);
+    }
+  };
+
+  useEffect(() => {
+    trayActionHandlerRef.current = (action) => void onTrayAction(action);
+  });
+
+  useEffect(() => {
+    if (typeof window === "undefined") {
+      return;
+    }
+
+    // The shell opens this page with the action in the query when another page was showing.
+    const pendingAction = trayActionFromSearch(window.location.search);
+    if (pendingAction) {
+      const url = new URL(window.location.href);
+      url.searchParams.delete("trayAction");
+      url.searchParams.delete("sessionId");
+      window.history.replaceState(null, "", url);
+      trayActionHandlerRef.current(pendingAction);
+    }
+
+    let isDisposed = false;
+    let unlisten: (() => void) | null = null;
+    void listenForTrayActions((action) => trayActionHandlerRef.current(action)).then((stop) => {
+      if (isDisposed) {
+        stop();
+      } else {
+        unlisten = stop;
+      }
+    });
+
+    return () => {
+      isDisposed = true;
+      unlisten?.();
+    };
+  }, []);
+
   const onProceedToPayment = async () => {
     if (!parkedSnapshot) {
       return;
@@ -1120,6 +1242,17 @@ export function ZoneLookupForm() {
           residentialRecommendations={destinationResult.residentialRecommendations}
         />
       ) : null}
+
+      {parkedMapLocation ? (
+        <DestinationMapModal
+          isOpen
+          onClose={() => setParkedMapLocation(null)}
+          destination={parkedMapLocation}
+          paidRecommendations={[]}
+          residentialRecommendations={[]}
+          title="Parked Location"
+        />
+      ) : null}
     </section>
   );
 }
@@ -1,3 +1,5 @@
+import { isTauriRuntime, tryInvokeTauri, type TauriCommandError, type TauriCommandErrorCode, type TauriFieldError } from "@/lib/tauri-invoke";
+
 export type StoredPaymentProfile = {
   cardNumber: string;
   cardExpiration: string;
@@ -5,6 +7,28 @@ export type StoredPaymentProfile = {
   license: string;
 };
 
+export type PaymentProfileSummary = {
+  id: string;
+  label: string;
+  brand: "visa" | "mastercard" | "amex" | "discover" | "dinersClub" | "jcb" | "unknown";
+  lastFour: string;
+  cardExpiration: string;
+  vehicleId: string | null;
+  plate: string | null;
+};
+
+export class PaymentProfileStorageError extends Error {
+  readonly code: TauriCommandErrorCode;
+  readonly fields: TauriFieldError[];
+
+  constructor(error: TauriCommandError) {
+    super(error.message);
+    this.name = "PaymentProfileStorageError";
+    this.code = error.code;
+    this.fields = error.fields ?? [];
+  }
+}
+
 const PAYMENT_PROFILE_STORAGE_KEY = "parkos.paymentProfile.v1";
 
 function normalizeStoredPaymentProfile(value: Partial<StoredPaymentProfile> | null | undefined): StoredPaymentProfile | null {
@@ -29,27 +53,19 @@ function normalizeStoredPaymentProfile(value: Partial<StoredPaymentProfile> | nu
   };
 }
 
-async function tryInvokeTauri<T>(command: string, args?: Record<string, unknown>): Promise<{ ok: true; value: T } | { ok: false }> {
-  if (typeof window === "undefined") {
-    return { ok: false };
-  }
-
-  try {
-    const { invoke } = await import("@tauri-apps/api/core");
-    const value = await invoke<T>(command, args);
-    return { ok: true, value };
-  } catch {
-    return { ok: false };
-  }
-}
-
 export async function loadStoredPaymentProfile(): Promise<StoredPaymentProfile | null> {
   const tauriResult = await tryInvokeTauri<StoredPaymentProfile | null>("load_payment_profile");
   if (tauriResult.ok) {
     return normalizeStoredPaymentProfile(tauriResult.value);
   }
+  if (tauriResult.reason === "command") {
+    if (tauriResult.error.code === "not_found") {
+      return null;
+    }
+    throw new PaymentProfileStorageError(tauriResult.error);
+  }
 
-  if (typeof window === "undefined") {
+  if (typeof window === "undefined" || isTauriRuntime()) {
     return null;
   }
 
@@ -66,6 +82,21 @@ export async function loadStoredPaymentProfile(): Promise<StoredPaymentProfile |
   }
 }
 
+/**
+ * Masked view of the default card kept by the desktop app. The full number never reaches the
+ * webview; returns null outside Tauri or when nothing is stored.
+ */
+export async function loadPaymentProfileSummary(): Promise<PaymentProfileSummary | null> {
+  const tauriResult = await tryInvokeTauri<PaymentProfileSummary | null>("get_payment_profile_summary");
+  if (tauriResult.ok) {
+    return tauriResult.value;
+  }
+  if (tauriResult.reason === "command" && tauriResult.error.code !== "not_found") {
+    throw new PaymentProfileStorageError(tauriResult.error);
+  }
+  return null;
+}
+
 export async function saveStoredPaymentProfile(profile: StoredPaymentProfile): Promise<void> {
   const normalized = normalizeStoredPaymentProfile(profile);
   if (!normalized) {
@@ -76,8 +107,34 @@ export async function saveStoredPaymentProfile(profile: StoredPaymentProfile): P
   if (tauriResult.ok) {
     return;
   }
+  if (tauriResult.reason === "command") {
+    throw new PaymentProfileStorageError(tauriResult.error);
+  }
 
-  if (typeof window !== "undefined") {
+  // The browser build has no encrypted store; the desktop app never writes the card to localStorage.
+  if (typeof window !== "undefined" && !isTauriRuntime()) {
     window.localStorage.setItem(PAYMENT_PROFILE_STORAGE_KEY, JSON.stringify(normalized));
   }
 }
+
+export async function deleteStoredPaymentProfile(): Promise<void> {
+  const tauriResult = await tryInvokeTauri<void>("delete_payment_profile");
+  if (!tauriResult.ok && tauriResult.reason === "command") {
+    throw new PaymentProfileStorageError(tauriResult.error);
+  }
+
+  if (typeof window !== "undefined") {
+    window.localStorage.removeItem(PAYMENT_PROFILE_STORAGE_KEY);
+  }
+}
+
+export async function wipeLocalData(): Promise<void> {
+  const tauriResult = await tryInvokeTauri<void>("wipe_local_data");
+  if (!tauriResult.ok && tauriResult.reason === "command") {
+    throw new PaymentProfileStorageError(tauriResult.error);
+  }
+
+  if (typeof window !== "undefined") {
+    window.localStorage.removeItem(PAYMENT_PROFILE_STORAGE_KEY);
+  }
+}
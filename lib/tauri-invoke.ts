@@ -0,0 +1,72 @@
+export type TauriCommandErrorCode =
+  | "not_found"
+  | "corrupt"
+  | "permission_denied"
+  | "validation"
+  | "conflict"
+  | "unsupported"
+  | "io"
+  | "crypto"
+  | "key_missing"
+  | "database"
+  | "internal";
+
+export type TauriFieldError = {
+  field: string;
+  message: string;
+};
+
+export type TauriCommandError = {
+  code: TauriCommandErrorCode;
+  message: string;
+  fields?: TauriFieldError[];
+};
+
+export type TauriInvokeResult<T> =
+  | { ok: true; value: T }
+  | { ok: false; reason: "unavailable" }
+  | { ok: false; reason: "command"; error: TauriCommandError };
+
+function isTauriCommandError(value: unknown): value is TauriCommandError {
+  return (
+    typeof value === "object" &&
+    value !== null &&
+    typeof (value as TauriCommandError).code === "string" &&
+    typeof (value as TauriCommandError).message === "string"
+  );
+}
+
+export function isTauriRuntime(): boolean {
+  return typeof window !== "undefined" && "__TAURI_INTERNALS__" in window;
+}
+
+/**
+ * Tauri rejects with a plain string when the call never reaches the command: an ACL denial, bad
+ * arguments or an unknown command.
+ */
+function toTauriCommandError(error: unknown): TauriCommandError {
+  const message = typeof error === "string" ? error : error instanceof Error ? error.message : String(error);
+  return {
+    code: /not allowed|denied/i.test(message) ? "permission_denied" : "internal",
+    message,
+  };
+}
+
+/**
+ * Invokes a command of the desktop shell. Resolves to `unavailable` only in the browser build, so
+ * callers can fall back to the HTTP route or local storage; inside the desktop app every failure is
+ * a typed `command` error.
+ */
+export async function tryInvokeTauri<T>(command: string, args?: Record<string, unknown>): Promise<TauriInvokeResult<T>> {
+  if (!isTauriRuntime()) {
+    return { ok: false, reason: "unavailable" };
+  }
+
+  try {
+    const { invoke } = await import("@tauri-apps/api/core");
+    const value = await invoke<T>(command, args);
+    return { ok: true, value };
+  } catch (error) {
+    return { ok: false, reason: "command", error: isTauriCommandError(error) ? error : toTauriCommandError(error) };
+  }
+}
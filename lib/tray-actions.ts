@@ -0,0 +1,44 @@
+import { isTauriRuntime } from "@/lib/tauri-invoke";
+
+/** Event the desktop shell emits when a tray menu item is picked; see `src-tauri/src/tray.rs`. */
+const TRAY_ACTION_EVENT = "tray-action";
+
+export type TrayActionName = "parked" | "renew" | "show-parked-location";
+
+export type TrayAction = {
+  action: TrayActionName;
+  /** The session the tray shows, if any. */
+  sessionId: string | null;
+};
+
+function isTrayActionName(value: string | null): value is TrayActionName {
+  return value === "parked" || value === "renew" || value === "show-parked-location";
+}
+
+/**
+ * Reads `?trayAction=` and `&sessionId=`, which the shell sets when a tray action opens the parking
+ * page from another page.
+ */
+export function trayActionFromSearch(search: string): TrayAction | null {
+  const params = new URLSearchParams(search);
+  const action = params.get("trayAction");
+  if (!isTrayActionName(action)) {
+    return null;
+  }
+
+  return { action, sessionId: params.get("sessionId")?.trim() || null };
+}
+
+/** Calls `handler` for each tray action while the page is open. Resolves to the unsubscribe function. */
+export async function listenForTrayActions(handler: (action: TrayAction) => void): Promise<() => void> {
+  if (!isTauriRuntime()) {
+    return () => {};
+  }
+
+  const { listen } = await import("@tauri-apps/api/event");
+  return listen<TrayAction>(TRAY_ACTION_EVENT, (event) => {
+    if (isTrayActionName(event.payload.action)) {
+      handler(event.payload);
+    }
+  });
+}
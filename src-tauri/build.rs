@@ -1,3 +1,37 @@
+/// Every app command gets generated `allow-*`/`deny-*` permissions, so a window can only invoke the
+/// commands its capability in `capabilities/` lists.
+const COMMANDS: &[&str] = &[
+  "load_payment_profile",
+  "save_payment_profile",
+  "get_payment_profile_summary",
+  "list_payment_profiles",
+  "create_payment_profile",
+  "update_payment_profile",
+  "remove_payment_profile",
+  "set_default_payment_profile",
+  "create_vehicle",
+  "update_vehicle",
+  "remove_vehicle",
+  "delete_payment_profile",
+  "wipe_local_data",
+  "export_diagnostics",
+  "lookup_current_zone",
+  "recommend_parking",
+  "estimate_parking_cost",
+  "is_enforced",
+  "parse_trip_prompt",
+  "search_destinations",
+  "capture_parking_session",
+  "activate_parking_session",
+  "renew_parking_session",
+  "cancel_parking_session",
+  "list_parking_sessions",
+  "resume_parking_session",
+];
+
 fn main() {
-  tauri_build::build()
+  tauri_build::try_build(
+    tauri_build::Attributes::new().app_manifest(tauri_build::AppManifest::new().commands(COMMANDS)),
+  )
+  .expect("failed to run tauri-build");
 }
@@ -0,0 +1,134 @@
+//! US federal holidays plus the extra ones San Luis Obispo city offices close for. A fixed-date
+//! holiday on a weekend is also observed on a weekday: a Saturday one the Friday before, a Sunday
+//! one the Monday after. Both days count as the holiday.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+const FIXED: [(u32, u32, &str); 6] = [
+  (1, 1, "New Year's Day"),
+  (3, 31, "Cesar Chavez Day"),
+  (6, 19, "Juneteenth"),
+  (7, 4, "Independence Day"),
+  (11, 11, "Veterans Day"),
+  (12, 25, "Christmas Day"),
+];
+/// `(month, weekday, n)`: the `n`th `weekday` of the month.
+const FLOATING: [(u32, Weekday, u8, &str); 5] = [
+  (1, Weekday::Mon, 3, "Martin Luther King Jr. Day"),
+  (2, Weekday::Mon, 3, "Presidents' Day"),
+  (9, Weekday::Mon, 1, "Labor Day"),
+  (10, Weekday::Mon, 2, "Columbus Day"),
+  (11, Weekday::Thu, 4, "Thanksgiving Day"),
+];
+const MEMORIAL_DAY: &str = "Memorial Day";
+const DAY_AFTER_THANKSGIVING: &str = "Day after Thanksgiving";
+
+/// Every holiday name the calendar knows.
+pub fn names() -> impl Iterator<Item = &'static str> {
+  FIXED
+    .iter()
+    .map(|(_, _, name)| *name)
+    .chain(FLOATING.iter().map(|(_, _, _, name)| *name))
+    .chain([MEMORIAL_DAY, DAY_AFTER_THANKSGIVING])
+}
+
+/// Names of the holidays that fall on or are observed on `date`.
+pub fn observed_on(date: NaiveDate) -> Vec<&'static str> {
+  // New Year's Day on a Saturday is observed on December 31 of the year before.
+  [date.year(), date.year() + 1]
+    .into_iter()
+    .flat_map(observed_holidays)
+    .filter(|(observed, _)| *observed == date)
+    .map(|(_, name)| name)
+    .collect()
+}
+
+/// Dates of the holidays of `year` in date order, with a weekend holiday listed on both the day
+/// itself and the weekday it is observed on.
+pub fn observed_holidays(year: i32) -> Vec<(NaiveDate, &'static str)> {
+  let mut holidays: Vec<(NaiveDate, &'static str)> = FIXED
+    .into_iter()
+    .filter_map(|(month, day, name)| NaiveDate::from_ymd_opt(year, month, day).map(|date| (date, name)))
+    .flat_map(|(date, name)| {
+      let observed = observed(date);
+      std::iter::once((date, name)).chain((observed != date).then_some((observed, name)))
+    })
+    .collect();
+  holidays.extend(FLOATING.into_iter().filter_map(|(month, weekday, n, name)| {
+    Some((NaiveDate::from_weekday_of_month_opt(year, month, weekday, n)?, name))
+  }));
+  holidays.extend(last_weekday_of_month(year, 5, Weekday::Mon).map(|date| (date, MEMORIAL_DAY)));
+  holidays.extend(
+    NaiveDate::from_weekday_of_month_opt(year, 11, Weekday::Thu, 4)
+      .map(|thanksgiving| (thanksgiving + Duration::days(1), DAY_AFTER_THANKSGIVING)),
+  );
+  holidays.sort();
+  holidays
+}
+
+fn observed(date: NaiveDate) -> NaiveDate {
+  match date.weekday() {
+    Weekday::Sat => date - Duration::days(1),
+    Weekday::Sun => date + Duration::days(1),
+    _ => date,
+  }
+}
+
+fn last_weekday_of_month(year: i32, month: u32, weekday: Weekday) -> Option<NaiveDate> {
+  NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+    .or_else(|| NaiveDate::from_weekday_of_month_opt(year, month, weekday, 4))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+  }
+
+  #[test]
+  fn weekend_holidays_keep_the_day_and_add_the_observed_weekday() {
+    let cases = [
+      // Saturday, observed the Friday before.
+      (2026, "Independence Day", vec![date(2026, 7, 3), date(2026, 7, 4)]),
+      (2027, "Christmas Day", vec![date(2027, 12, 24), date(2027, 12, 25)]),
+      // Sunday, observed the Monday after.
+      (2027, "Independence Day", vec![date(2027, 7, 4), date(2027, 7, 5)]),
+      // Weekday, listed once.
+      (2026, "Christmas Day", vec![date(2026, 12, 25)]),
+      // Saturday January 1 is observed on December 31 of the year before.
+      (2028, "New Year's Day", vec![date(2027, 12, 31), date(2028, 1, 1)]),
+    ];
+    for (year, name, expected) in cases {
+      let dates: Vec<NaiveDate> = observed_holidays(year)
+        .into_iter()
+        .filter(|(_, holiday)| *holiday == name)
+        .map(|(date, _)| date)
+        .collect();
+      assert_eq!(dates, expected, "{name} {year}");
+    }
+  }
+
+  #[test]
+  fn floating_holidays() {
+    let holidays = observed_holidays(2026);
+    for (name, expected) in [
+      ("Martin Luther King Jr. Day", date(2026, 1, 19)),
+      ("Memorial Day", date(2026, 5, 25)),
+      ("Labor Day", date(2026, 9, 7)),
+      ("Thanksgiving Day", date(2026, 11, 26)),
+      ("Day after Thanksgiving", date(2026, 11, 27)),
+    ] {
+      assert!(holidays.contains(&(expected, name)), "{name}");
+    }
+    assert!(holidays.windows(2).all(|pair| pair[0] <= pair[1]));
+  }
+
+  #[test]
+  fn observed_on_looks_into_the_next_year() {
+    assert_eq!(observed_on(date(2027, 12, 31)), vec!["New Year's Day"]);
+    assert_eq!(observed_on(date(2028, 1, 1)), vec!["New Year's Day"]);
+    assert!(observed_on(date(2026, 7, 6)).is_empty());
+  }
+}
@@ -0,0 +1,244 @@
+//! Enforcement calendar: the holidays meters are free on, and one-off changes for events and street
+//! closures from `data/slo-enforcement-calendar.json`. Everything is in San Luis Obispo local time.
+
+mod holidays;
+
+use crate::error::{CommandError, CommandResult};
+use crate::payment_profile::FieldError;
+use crate::rules;
+use crate::zones::{ZoneCategory, ZoneIndex};
+use chrono::{DateTime, LocalResult, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+pub const TIME_ZONE: Tz = chrono_tz::America::Los_Angeles;
+
+const OVERRIDE_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+const ENFORCEMENT_CALENDAR_JSON: &str = include_str!("../../../data/slo-enforcement-calendar.json");
+
+/// Bundled data file name and contents, for the diagnostics bundle.
+pub const BUNDLED_DATASET: (&str, &str) = ("slo-enforcement-calendar.json", ENFORCEMENT_CALENDAR_JSON);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CalendarFile {
+  meter_holidays: Vec<String>,
+  overrides: Vec<OverrideEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OverrideEntry {
+  id: String,
+  name: String,
+  effect: OverrideEffect,
+  zones: Option<Vec<String>>,
+  /// Local wall-clock time, like `2026-12-04T16:00:00`.
+  starts_at: String,
+  ends_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverrideEffect {
+  /// Meters or permit hours are not enforced, whatever the weekly schedule says.
+  Free,
+  /// Enforced at the zone's usual rate, even outside its weekly hours.
+  Enforced,
+  /// No parking at all, e.g. a street closed for a parade.
+  Closed,
+}
+
+/// A one-off change to enforcement. Applies to the listed zone numbers, or to every paid zone when
+/// the entry lists none.
+#[derive(Debug, Clone)]
+pub struct Override {
+  pub name: String,
+  pub effect: OverrideEffect,
+  zones: Option<Vec<String>>,
+  pub starts_at: DateTime<Tz>,
+  pub ends_at: DateTime<Tz>,
+}
+
+impl Override {
+  fn applies_to(&self, zone: &str, category: ZoneCategory) -> bool {
+    match &self.zones {
+      Some(zones) => zones.iter().any(|listed| listed.eq_ignore_ascii_case(zone)),
+      None => matches!(category, ZoneCategory::Paid),
+    }
+  }
+
+  pub fn covers(&self, at: DateTime<Tz>) -> bool {
+    self.starts_at <= at && at < self.ends_at
+  }
+}
+
+/// Parsed once at startup and shared through Tauri state, like the zone index.
+pub struct EnforcementCalendar {
+  meter_holidays: Vec<String>,
+  overrides: Vec<Override>,
+}
+
+/// What the calendar says for one zone.
+pub struct ZoneCalendar<'a> {
+  calendar: &'a EnforcementCalendar,
+  overrides: Vec<&'a Override>,
+}
+
+impl EnforcementCalendar {
+  pub fn load_bundled() -> Result<Self, String> {
+    let file = serde_json::from_str::<CalendarFile>(ENFORCEMENT_CALENDAR_JSON)
+      .map_err(|error| format!("Failed to parse bundled enforcement calendar: {error}"))?;
+
+    if let Some(unknown) = file
+      .meter_holidays
+      .iter()
+      .find(|name| !holidays::names().any(|known| known == name.as_str()))
+    {
+      return Err(format!("Unknown holiday {unknown:?} in bundled enforcement calendar"));
+    }
+
+    let overrides = file
+      .overrides
+      .into_iter()
+      .map(|entry| {
+        let parse = |value: &str| {
+          NaiveDateTime::parse_from_str(value, OVERRIDE_TIME_FORMAT)
+            .map(local_datetime)
+            .map_err(|error| format!("Invalid time {value:?} in enforcement override {}: {error}", entry.id))
+        };
+        let starts_at = parse(&entry.starts_at)?;
+        let ends_at = parse(&entry.ends_at)?;
+        if ends_at <= starts_at {
+          return Err(format!("Enforcement override {} ends before it starts", entry.id));
+        }
+        Ok(Override {
+          name: entry.name,
+          effect: entry.effect,
+          zones: entry.zones,
+          starts_at,
+          ends_at,
+        })
+      })
+      .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(EnforcementCalendar {
+      meter_holidays: file.meter_holidays,
+      overrides,
+    })
+  }
+
+  pub fn for_zone(&self, zone: &str, category: ZoneCategory) -> ZoneCalendar<'_> {
+    ZoneCalendar {
+      calendar: self,
+      overrides: self
+        .overrides
+        .iter()
+        .filter(|entry| entry.applies_to(zone, category))
+        .collect(),
+    }
+  }
+
+  /// The holiday on `date` that meters are free for, if any. A weekend holiday makes both the day
+  /// itself and its observed weekday free.
+  pub fn meter_holiday(&self, date: NaiveDate) -> Option<&'static str> {
+    holidays::observed_on(date)
+      .into_iter()
+      .find(|name| self.meter_holidays.iter().any(|listed| listed == name))
+  }
+}
+
+impl<'a> ZoneCalendar<'a> {
+  pub fn meter_holiday(&self, date: NaiveDate) -> Option<&'static str> {
+    self.calendar.meter_holiday(date)
+  }
+
+  /// Overrides for this zone with `effect`, in file order.
+  pub fn overrides(&self, effect: OverrideEffect) -> impl Iterator<Item = &'a Override> + '_ {
+    self.overrides.iter().copied().filter(move |entry| entry.effect == effect)
+  }
+}
+
+/// Resolves a wall-clock time in `TIME_ZONE`. Ambiguous times take the first occurrence and times
+/// skipped by the spring-forward transition the hour after.
+pub fn local_datetime(naive: NaiveDateTime) -> DateTime<Tz> {
+  match TIME_ZONE.from_local_datetime(&naive) {
+    LocalResult::Single(time) => time,
+    LocalResult::Ambiguous(earliest, _) => earliest,
+    LocalResult::None => local_datetime(naive + chrono::Duration::hours(1)),
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnforcementStatus {
+  pub zone_number: String,
+  pub category: ZoneCategory,
+  pub at: String,
+  /// Meters must be paid (or, in a residential district, a permit is needed) at `at`.
+  pub enforced: bool,
+  /// `false` while a closure covers the zone.
+  pub parking_allowed: bool,
+  pub reason: String,
+  /// When `enforced` next flips, if that is within the next week.
+  pub changes_at: Option<String>,
+  pub warnings: Vec<String>,
+}
+
+/// Whether parking in PayByPhone zone `zone` (or residential district id) is enforced at `at`, an
+/// RFC 3339 timestamp.
+#[tauri::command]
+pub fn is_enforced(
+  zones: tauri::State<'_, ZoneIndex>,
+  calendar: tauri::State<'_, EnforcementCalendar>,
+  zone: String,
+  at: String,
+) -> CommandResult<EnforcementStatus> {
+  let zone = zone.trim();
+  let mut fields = Vec::new();
+  if zone.is_empty() {
+    fields.push(FieldError {
+      field: "zone".to_string(),
+      message: "Zone number is required.".to_string(),
+    });
+  }
+  let at = DateTime::parse_from_rfc3339(at.trim()).ok();
+  if at.is_none() {
+    fields.push(FieldError {
+      field: "at".to_string(),
+      message: "Time must be a timestamp like 2026-02-22T20:00:00-08:00.".to_string(),
+    });
+  }
+
+  match at {
+    Some(at) if fields.is_empty() => rules::enforcement_status(&zones, &calendar, zone, at.with_timezone(&TIME_ZONE)),
+    _ => Err(CommandError::Validation { fields }),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn meter_holidays_cover_the_day_and_the_observed_weekday() {
+    let calendar = EnforcementCalendar::load_bundled().unwrap();
+    let cases = [
+      ((2026, 7, 3), Some("Independence Day")),
+      ((2026, 7, 4), Some("Independence Day")),
+      ((2027, 12, 24), Some("Christmas Day")),
+      ((2027, 12, 25), Some("Christmas Day")),
+      ((2027, 12, 31), Some("New Year's Day")),
+      ((2028, 1, 1), Some("New Year's Day")),
+      ((2026, 11, 26), Some("Thanksgiving Day")),
+      // Holidays meters are enforced on, and ordinary days.
+      ((2026, 11, 11), None),
+      ((2026, 11, 27), None),
+      ((2026, 7, 6), None),
+    ];
+    for ((year, month, day), expected) in cases {
+      let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+      assert_eq!(calendar.meter_holiday(date), expected, "{date}");
+    }
+  }
+}
@@ -0,0 +1,180 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use crate::error::{CommandError, CommandResult};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+const MAGIC: &[u8; 4] = b"PKOS";
+const FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1 + NONCE_LEN;
+
+const KEY_MISSING_MESSAGE: &str =
+  "Payment profile encryption key is missing on this device; delete the payment profile and enter the card again";
+const DECRYPT_FAILED_MESSAGE: &str =
+  "Failed to decrypt payment profile: the key does not match or the file was tampered with";
+
+/// Reads the local encryption key. Never creates one: a fresh key could not decrypt an existing
+/// profile, e.g. one copied or synced from another machine without its key.
+pub fn load_key(path: &Path) -> CommandResult<Key> {
+  let raw = match fs::read(path) {
+    Ok(raw) => raw,
+    Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+      return Err(CommandError::KeyMissing(KEY_MISSING_MESSAGE.to_string()))
+    }
+    Err(error) => return Err(CommandError::io("Failed to read encryption key file", error)),
+  };
+  if raw.len() != KEY_LEN {
+    return Err(CommandError::Crypto("Encryption key file is malformed".to_string()));
+  }
+  Ok(*Key::from_slice(&raw))
+}
+
+/// Reads the local encryption key, generating one with owner-only permissions on first use. Only
+/// for writes; reads go through `load_key`.
+pub fn load_or_create_key(path: &Path) -> CommandResult<Key> {
+  if path.exists() {
+    return load_key(path);
+  }
+
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)
+      .map_err(|error| CommandError::io("Failed to create encryption key directory", error))?;
+  }
+
+  let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+  let mut file = create_private_file(path)
+    .map_err(|error| CommandError::io("Failed to create encryption key file", error))?;
+  file
+    .write_all(key.as_slice())
+    .and_then(|_| file.sync_all())
+    .map_err(|error| CommandError::io("Failed to write encryption key file", error))?;
+
+  Ok(key)
+}
+
+#[cfg(unix)]
+fn create_private_file(path: &Path) -> std::io::Result<fs::File> {
+  use std::os::unix::fs::OpenOptionsExt;
+
+  fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)
+}
+
+#[cfg(not(unix))]
+fn create_private_file(path: &Path) -> std::io::Result<fs::File> {
+  fs::OpenOptions::new().write(true).create_new(true).open(path)
+}
+
+/// Encrypts `plaintext` into a self-describing blob: magic, format version, nonce, ciphertext.
+pub fn encrypt(key: &Key, plaintext: &[u8]) -> CommandResult<Vec<u8>> {
+  let cipher = XChaCha20Poly1305::new(key);
+  let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+  let ciphertext = cipher
+    .encrypt(&nonce, plaintext)
+    .map_err(|_| CommandError::Crypto("Failed to encrypt payment profile".to_string()))?;
+
+  let mut sealed = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+  sealed.extend_from_slice(MAGIC);
+  sealed.push(FORMAT_VERSION);
+  sealed.extend_from_slice(nonce.as_slice());
+  sealed.extend_from_slice(&ciphertext);
+  Ok(sealed)
+}
+
+pub fn decrypt(key: &Key, sealed: &[u8]) -> CommandResult<Vec<u8>> {
+  if sealed.len() < HEADER_LEN || &sealed[..MAGIC.len()] != MAGIC {
+    return Err(CommandError::Corrupt(
+      "Payment profile file is not in the encrypted format".to_string(),
+    ));
+  }
+  if sealed[MAGIC.len()] != FORMAT_VERSION {
+    return Err(CommandError::Unsupported(format!(
+      "Unsupported payment profile encryption format version {}",
+      sealed[MAGIC.len()]
+    )));
+  }
+
+  let nonce = XNonce::from_slice(&sealed[MAGIC.len() + 1..HEADER_LEN]);
+  XChaCha20Poly1305::new(key)
+    .decrypt(nonce, &sealed[HEADER_LEN..])
+    .map_err(|_| CommandError::Crypto(DECRYPT_FAILED_MESSAGE.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  fn key() -> Key {
+    XChaCha20Poly1305::generate_key(&mut OsRng)
+  }
+
+  #[test]
+  fn round_trip() {
+    let key = key();
+    let sealed = encrypt(&key, b"4242424242424242").unwrap();
+    assert_eq!(&sealed[..MAGIC.len()], MAGIC);
+    assert!(!sealed.windows(16).any(|window| window == b"4242424242424242"));
+    assert_eq!(decrypt(&key, &sealed).unwrap(), b"4242424242424242");
+    // A fresh nonce each time.
+    assert_ne!(encrypt(&key, b"4242424242424242").unwrap(), sealed);
+  }
+
+  #[test]
+  fn tampered_ciphertext_is_rejected() {
+    let key = key();
+    let mut sealed = encrypt(&key, b"payment profile").unwrap();
+    let last = sealed.len() - 1;
+    sealed[last] ^= 1;
+    assert!(matches!(decrypt(&key, &sealed), Err(CommandError::Crypto(_))));
+  }
+
+  #[test]
+  fn wrong_key_is_rejected() {
+    let sealed = encrypt(&key(), b"payment profile").unwrap();
+    assert!(matches!(decrypt(&key(), &sealed), Err(CommandError::Crypto(_))));
+  }
+
+  #[test]
+  fn bad_header() {
+    let key = key();
+    let sealed = encrypt(&key, b"payment profile").unwrap();
+
+    let mut bad_magic = sealed.clone();
+    bad_magic[0] = b'X';
+    assert!(matches!(decrypt(&key, &bad_magic), Err(CommandError::Corrupt(_))));
+    assert!(matches!(decrypt(&key, &sealed[..HEADER_LEN - 1]), Err(CommandError::Corrupt(_))));
+    assert!(matches!(decrypt(&key, b"{\"schemaVersion\":3}"), Err(CommandError::Corrupt(_))));
+
+    let mut future = sealed;
+    future[MAGIC.len()] = FORMAT_VERSION + 1;
+    assert!(matches!(decrypt(&key, &future), Err(CommandError::Unsupported(_))));
+  }
+
+  #[test]
+  fn load_key_never_creates_one() {
+    let dir = TempDir::new();
+    let path = dir.join("payment_profile.key");
+    assert!(matches!(load_key(&path), Err(CommandError::KeyMissing(_))));
+    assert!(!path.exists());
+  }
+
+  #[test]
+  fn load_or_create_key_persists_the_key() {
+    let dir = TempDir::new();
+    let path = dir.join("nested").join("payment_profile.key");
+    let created = load_or_create_key(&path).unwrap();
+    assert_eq!(load_key(&path).unwrap(), created);
+    assert_eq!(load_or_create_key(&path).unwrap(), created);
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    fs::write(&path, b"short").unwrap();
+    assert!(matches!(load_key(&path), Err(CommandError::Crypto(_))));
+  }
+}
@@ -0,0 +1,128 @@
+//! `parkos://` links. SMS reminders link to `parkos://resume?token=…`, which opens the renew flow for
+//! that session instead of the hosted `/api/parking/session/resume` page.
+
+use crate::error::{CommandError, CommandResult};
+use crate::paths::DATA_DIR_FLAG;
+use crate::sessions::{self, SessionStore};
+use crate::tray::{self, MAIN_WINDOW};
+use tauri::{Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+const SCHEME: &str = "parkos";
+/// Page that reads `?resume=` and restores the session, like the SMS links' `/parking?resume=…`.
+const RESUME_PAGE: &str = "/parking";
+
+/// Handles links that arrive while the app runs, and the one it was launched with.
+pub fn listen(app: &tauri::AppHandle) {
+  // macOS only learns the scheme from the app bundle; elsewhere register it at runtime so dev builds
+  // and unpacked AppImages receive links too.
+  #[cfg(any(windows, target_os = "linux"))]
+  if let Err(error) = app.deep_link().register_all() {
+    log::warn!("Failed to register {SCHEME}:// links: {error}");
+  }
+
+  let handle = app.clone();
+  app.deep_link().on_open_url(move |event| open_urls(&handle, &event.urls()));
+
+  match app.deep_link().get_current() {
+    Ok(Some(urls)) => open_urls(app, &urls),
+    Ok(None) => {}
+    Err(error) => log::warn!("Failed to read launch link: {error}"),
+  }
+}
+
+pub fn open_urls(app: &tauri::AppHandle, urls: &[Url]) {
+  for url in urls {
+    if let Err(error) = open_url(app, url) {
+      log::warn!("Ignoring {SCHEME}:// link: {error}");
+    }
+  }
+}
+
+/// `parkos://` links among the arguments of a second launch. The program path, flags like
+/// `--data-dir` with their values, and anything else are left out.
+pub fn forwarded_urls(args: &[String]) -> Vec<Url> {
+  let mut urls = Vec::new();
+  let mut args = args.iter().skip(1);
+  while let Some(arg) = args.next() {
+    if arg == DATA_DIR_FLAG {
+      args.next();
+    } else if arg.starts_with('-') {
+      continue;
+    } else {
+      match Url::parse(arg) {
+        Ok(url) if url.scheme() == SCHEME => urls.push(url),
+        _ => log::info!("Ignoring launch argument that is not a {SCHEME}:// link"),
+      }
+    }
+  }
+  urls
+}
+
+fn open_url(app: &tauri::AppHandle, url: &Url) -> CommandResult<()> {
+  if url.scheme() != SCHEME {
+    return Err(CommandError::Unsupported(format!("Unsupported link scheme {}", url.scheme())));
+  }
+
+  match url.host_str() {
+    Some("resume") => {
+      let token = url
+        .query_pairs()
+        .find(|(key, _)| key == "token")
+        .map(|(_, value)| value.trim().to_string())
+        .unwrap_or_default();
+      resume(app, &token)
+    }
+    other => Err(CommandError::Unsupported(format!(
+      "Unsupported link action {}",
+      other.unwrap_or_default()
+    ))),
+  }
+}
+
+/// Checks the token against the local session store, then points the main window at the renew flow.
+fn resume(app: &tauri::AppHandle, token: &str) -> CommandResult<()> {
+  let session = sessions::resume(&app.state::<SessionStore>(), token)?;
+  log::info!("Resuming parking session {} from link", session.id);
+
+  let window = app
+    .get_webview_window(MAIN_WINDOW)
+    .ok_or_else(|| CommandError::Internal("Main window is not open".to_string()))?;
+  let mut target = window
+    .url()
+    .map_err(|error| CommandError::Internal(format!("Failed to read window URL: {error}")))?;
+  target.set_path(RESUME_PAGE);
+  target.set_fragment(None);
+  target.query_pairs_mut().clear().append_pair("resume", token.trim());
+  window
+    .navigate(target)
+    .map_err(|error| CommandError::Internal(format!("Failed to open renew flow: {error}")))?;
+
+  tray::show_main_window(app);
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn args(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+  }
+
+  #[test]
+  fn forwarded_urls_keep_only_parkos_links() {
+    let cases: &[(&[&str], &[&str])] = &[
+      (&["parkos"], &[]),
+      (&["parkos://resume?token=abc"], &[]),
+      (&["parkos", "parkos://resume?token=abc"], &["parkos://resume?token=abc"]),
+      (&["parkos", "--data-dir", "parkos://not-a-link", "parkos://resume?token=abc"], &["parkos://resume?token=abc"]),
+      (&["parkos", "--data-dir=/tmp/parkos", "--verbose", "parkos://resume?token=abc"], &["parkos://resume?token=abc"]),
+      (&["parkos", "https://example.com", "notes.txt", "C:\\parkos"], &[]),
+    ];
+    for (input, expected) in cases {
+      let urls: Vec<String> = forwarded_urls(&args(input)).iter().map(Url::to_string).collect();
+      assert_eq!(urls, args(expected), "{input:?}");
+    }
+  }
+}
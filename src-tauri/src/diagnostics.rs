@@ -0,0 +1,332 @@
+//! Support bundle: a zip of redacted logs, versions, dataset checksums, recent zone lookups and the
+//! session database schema version. Payment profile files are never read.
+
+use crate::calendar;
+use crate::error::{CommandError, CommandResult};
+use crate::payment_profile::FieldError;
+use crate::sessions::SessionStore;
+use crate::trip;
+use crate::zones::{self, CurrentZoneResponse};
+use crate::{logging, paths};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+const RECENT_LOOKUP_LIMIT: usize = 50;
+/// Three decimals is about 100 m: enough to check a zone boundary, not enough to pinpoint a home.
+const COORDINATE_SCALE: f64 = 1_000.0;
+
+/// The last `RECENT_LOOKUP_LIMIT` zone lookups, with coordinates already rounded. Kept in memory
+/// only.
+#[derive(Default)]
+pub struct LookupHistory {
+  lookups: Mutex<VecDeque<CurrentZoneResponse>>,
+}
+
+impl LookupHistory {
+  pub fn record(&self, lookup: &CurrentZoneResponse) {
+    let mut lookup = lookup.clone();
+    lookup.location.lat = round_coordinate(lookup.location.lat);
+    lookup.location.lng = round_coordinate(lookup.location.lng);
+    lookup.location.accuracy_meters = lookup.location.accuracy_meters.map(f64::round);
+
+    if let Ok(mut lookups) = self.lookups.lock() {
+      if lookups.len() == RECENT_LOOKUP_LIMIT {
+        lookups.pop_front();
+      }
+      lookups.push_back(lookup);
+    }
+  }
+
+  fn snapshot(&self) -> Vec<CurrentZoneResponse> {
+    self
+      .lookups
+      .lock()
+      .map(|lookups| lookups.iter().cloned().collect())
+      .unwrap_or_default()
+  }
+}
+
+fn round_coordinate(value: f64) -> f64 {
+  (value * COORDINATE_SCALE).round() / COORDINATE_SCALE
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+  generated_at: String,
+  app: AppInfo,
+  datasets: Vec<DatasetInfo>,
+  session_store: SessionStoreInfo,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppInfo {
+  product_name: Option<String>,
+  identifier: String,
+  version: String,
+  tauri_version: &'static str,
+  os: &'static str,
+  arch: &'static str,
+  debug_build: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DatasetInfo {
+  file: &'static str,
+  bytes: usize,
+  sha256: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionStoreInfo {
+  schema_version: Option<usize>,
+  supported_schema_version: Option<usize>,
+  error: Option<String>,
+}
+
+/// Writes the bundle to `path` (a `.zip` extension is added if missing) and returns where it went.
+/// Refuses to replace a file that is already there.
+#[tauri::command]
+pub fn export_diagnostics(
+  app: tauri::AppHandle,
+  sessions: tauri::State<'_, SessionStore>,
+  history: tauri::State<'_, LookupHistory>,
+  path: String,
+) -> CommandResult<String> {
+  let path = export_path(&path)?;
+
+  let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+  add_json(&mut archive, "manifest.json", &manifest(&app, &sessions))?;
+  add_json(&mut archive, "recent-zone-lookups.json", &history.snapshot())?;
+  for (name, contents) in redacted_logs(&app)? {
+    add_file(&mut archive, &format!("logs/{name}"), contents.as_bytes())?;
+  }
+  let contents = archive
+    .finish()
+    .map_err(|error| CommandError::Io(format!("Failed to build diagnostics bundle: {error}")))?
+    .into_inner();
+
+  write_new_file(&path, &contents)?;
+  log::info!("Exported diagnostics bundle");
+  Ok(path.to_string_lossy().into_owned())
+}
+
+fn export_path(path: &str) -> CommandResult<PathBuf> {
+  let mut path = PathBuf::from(path.trim());
+  if !path.is_absolute() || path.file_name().is_none() {
+    return Err(CommandError::Validation {
+      fields: vec![FieldError {
+        field: "path".to_string(),
+        message: "Choose a full file path for the diagnostics bundle.".to_string(),
+      }],
+    });
+  }
+  if !path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("zip")) {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".zip");
+    path.set_file_name(file_name);
+  }
+  Ok(path)
+}
+
+/// Creates `path` with `contents`, failing with `Conflict` if it exists. A partly written file is
+/// removed again.
+fn write_new_file(path: &Path, contents: &[u8]) -> CommandResult<()> {
+  let mut file = fs::OpenOptions::new()
+    .write(true)
+    .create_new(true)
+    .open(path)
+    .map_err(|error| match error.kind() {
+      std::io::ErrorKind::AlreadyExists => {
+        CommandError::Conflict(format!("{} already exists; choose another file name.", path.display()))
+      }
+      _ => CommandError::io("Failed to save diagnostics bundle", error),
+    })?;
+
+  if let Err(error) = file.write_all(contents).and_then(|_| file.sync_all()) {
+    drop(file);
+    let _ = fs::remove_file(path);
+    return Err(CommandError::io("Failed to save diagnostics bundle", error));
+  }
+  Ok(())
+}
+
+fn manifest(app: &tauri::AppHandle, sessions: &SessionStore) -> Manifest {
+  let config = app.config();
+  let session_store = match sessions.schema_versions() {
+    Ok((schema_version, supported_schema_version)) => SessionStoreInfo {
+      schema_version: Some(schema_version),
+      supported_schema_version: Some(supported_schema_version),
+      error: None,
+    },
+    Err(error) => SessionStoreInfo {
+      schema_version: None,
+      supported_schema_version: None,
+      error: Some(logging::redact(&error.to_string())),
+    },
+  };
+
+  Manifest {
+    generated_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+    app: AppInfo {
+      product_name: config.product_name.clone(),
+      identifier: config.identifier.clone(),
+      version: app.package_info().version.to_string(),
+      tauri_version: tauri::VERSION,
+      os: std::env::consts::OS,
+      arch: std::env::consts::ARCH,
+      debug_build: cfg!(debug_assertions),
+    },
+    datasets: zones::BUNDLED_DATASETS
+      .iter()
+      .chain([&calendar::BUNDLED_DATASET, &trip::BUNDLED_DATASET])
+      .map(|(file, contents)| DatasetInfo {
+        file,
+        bytes: contents.len(),
+        sha256: format!("{:x}", Sha256::digest(contents.as_bytes())),
+      })
+      .collect(),
+    session_store,
+  }
+}
+
+/// Log files from the app log dir, passed through `logging::redact` again in case a file predates
+/// redaction or was written by something else.
+fn redacted_logs(app: &tauri::AppHandle) -> CommandResult<Vec<(String, String)>> {
+  let dir = paths::app_log_dir(app)?;
+  let entries = match fs::read_dir(&dir) {
+    Ok(entries) => entries,
+    Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(error) => return Err(CommandError::io("Failed to list log files", error)),
+  };
+
+  let mut logs = Vec::new();
+  for entry in entries {
+    let path = entry.map_err(|error| CommandError::io("Failed to list log files", error))?.path();
+    if !is_log_file(&path) {
+      continue;
+    }
+    let contents = fs::read(&path).map_err(|error| CommandError::io("Failed to read log file", error))?;
+    let redacted: Vec<String> = String::from_utf8_lossy(&contents).lines().map(logging::redact).collect();
+    let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    logs.push((name, redacted.join("\n")));
+  }
+  logs.sort();
+  Ok(logs)
+}
+
+fn is_log_file(path: &Path) -> bool {
+  path.is_file() && path.extension().is_some_and(|extension| extension == "log")
+}
+
+fn add_json<T: Serialize>(archive: &mut ZipWriter<Cursor<Vec<u8>>>, name: &str, value: &T) -> CommandResult<()> {
+  let contents = serde_json::to_vec_pretty(value)
+    .map_err(|error| CommandError::Internal(format!("Failed to serialize {name}: {error}")))?;
+  add_file(archive, name, &contents)
+}
+
+fn add_file(archive: &mut ZipWriter<Cursor<Vec<u8>>>, name: &str, contents: &[u8]) -> CommandResult<()> {
+  archive
+    .start_file(name, SimpleFileOptions::default())
+    .map_err(|error| CommandError::Io(format!("Failed to add {name} to diagnostics bundle: {error}")))?;
+  archive
+    .write_all(contents)
+    .map_err(|error| CommandError::io("Failed to write diagnostics bundle", error))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+  use crate::zones::{CurrentZone, LookupLocation, MatchType, ZoneCategory};
+
+  fn lookup(lat: f64, lng: f64, accuracy_meters: Option<f64>) -> CurrentZoneResponse {
+    CurrentZoneResponse {
+      location: LookupLocation {
+        lat,
+        lng,
+        accuracy_meters,
+      },
+      zone: CurrentZone {
+        category: ZoneCategory::None,
+        match_type: MatchType::None,
+        distance_meters: None,
+        zone_number: None,
+        rate: None,
+        payment_eligible: false,
+        payment_entry_label: String::new(),
+        message: String::new(),
+      },
+      snapshot_at: "2026-02-23T20:00:00.000Z".to_string(),
+      warnings: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn export_paths() {
+    let dir = TempDir::new();
+    let cases = [
+      (dir.join("parkos-diagnostics"), Some(dir.join("parkos-diagnostics.zip"))),
+      (dir.join("bundle.zip"), Some(dir.join("bundle.zip"))),
+      (dir.join("bundle.ZIP"), Some(dir.join("bundle.ZIP"))),
+      (dir.join("bundle.tar"), Some(dir.join("bundle.tar.zip"))),
+      (PathBuf::from("bundle.zip"), None),
+      (PathBuf::from("logs/bundle"), None),
+      (PathBuf::from(""), None),
+    ];
+    for (input, expected) in cases {
+      let input = input.to_string_lossy().into_owned();
+      let result = export_path(&format!(" {input} "));
+      match expected {
+        Some(expected) => assert_eq!(result.unwrap(), expected, "{input}"),
+        None => assert!(matches!(result, Err(CommandError::Validation { .. })), "{input}"),
+      }
+    }
+  }
+
+  #[test]
+  fn existing_files_are_not_overwritten() {
+    let dir = TempDir::new();
+    let path = dir.join("bundle.zip");
+    write_new_file(&path, b"first").unwrap();
+    assert!(matches!(write_new_file(&path, b"second"), Err(CommandError::Conflict(_))));
+    assert_eq!(fs::read(&path).unwrap(), b"first");
+  }
+
+  #[test]
+  fn lookups_are_rounded() {
+    let history = LookupHistory::default();
+    history.record(&lookup(35.279_649, -120.663_451, Some(12.6)));
+    history.record(&lookup(35.280_5, -120.66, None));
+
+    let recorded: Vec<_> = history
+      .snapshot()
+      .into_iter()
+      .map(|lookup| (lookup.location.lat, lookup.location.lng, lookup.location.accuracy_meters))
+      .collect();
+    assert_eq!(recorded, [(35.28, -120.663, Some(13.0)), (35.281, -120.66, None)]);
+  }
+
+  #[test]
+  fn only_the_latest_lookups_are_kept() {
+    let history = LookupHistory::default();
+    for index in 0..RECENT_LOOKUP_LIMIT + 5 {
+      history.record(&lookup(index as f64, 0.0, None));
+    }
+
+    let lats: Vec<f64> = history.snapshot().iter().map(|lookup| lookup.location.lat).collect();
+    assert_eq!(lats.len(), RECENT_LOOKUP_LIMIT);
+    assert_eq!(lats[0], 5.0);
+    assert_eq!(lats[RECENT_LOOKUP_LIMIT - 1], (RECENT_LOOKUP_LIMIT + 4) as f64);
+  }
+}
@@ -0,0 +1,108 @@
+use crate::payment_profile::FieldError;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
+/// Error returned by every Tauri command. Serializes to `{ code, message, fields? }` where `code` is
+/// stable for the frontend to branch on and `message` is safe to show to the user.
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+  #[error("{0}")]
+  NotFound(String),
+  #[error("{0}")]
+  Corrupt(String),
+  #[error("{0}")]
+  PermissionDenied(String),
+  #[error("Some fields are invalid: {}", describe_fields(.fields))]
+  Validation { fields: Vec<FieldError> },
+  #[error("{0}")]
+  Conflict(String),
+  #[error("{0}")]
+  Unsupported(String),
+  #[error("{0}")]
+  Io(String),
+  #[error("{0}")]
+  Crypto(String),
+  /// The encrypted profile exists but its local key does not.
+  #[error("{0}")]
+  KeyMissing(String),
+  #[error("{0}")]
+  Database(String),
+  #[error("{0}")]
+  Internal(String),
+}
+
+impl CommandError {
+  pub fn code(&self) -> &'static str {
+    match self {
+      CommandError::NotFound(_) => "not_found",
+      CommandError::Corrupt(_) => "corrupt",
+      CommandError::PermissionDenied(_) => "permission_denied",
+      CommandError::Validation { .. } => "validation",
+      CommandError::Conflict(_) => "conflict",
+      CommandError::Unsupported(_) => "unsupported",
+      CommandError::Io(_) => "io",
+      CommandError::Crypto(_) => "crypto",
+      CommandError::KeyMissing(_) => "key_missing",
+      CommandError::Database(_) => "database",
+      CommandError::Internal(_) => "internal",
+    }
+  }
+
+  /// Classifies a filesystem error by kind, prefixing `context` (e.g. "Failed to read payment
+  /// profile file") to the message.
+  pub fn io(context: &str, error: std::io::Error) -> Self {
+    let message = format!("{context}: {error}");
+    match error.kind() {
+      std::io::ErrorKind::NotFound => CommandError::NotFound(message),
+      std::io::ErrorKind::PermissionDenied => CommandError::PermissionDenied(message),
+      _ => CommandError::Io(message),
+    }
+  }
+
+  /// Classifies a SQLite error, prefixing `context` (e.g. "Failed to list parking sessions") to the
+  /// message.
+  pub fn database(context: &str, error: rusqlite::Error) -> Self {
+    let message = format!("{context}: {error}");
+    match error.sqlite_error_code() {
+      Some(rusqlite::ErrorCode::ConstraintViolation) => CommandError::Conflict(message),
+      Some(rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase) => CommandError::Corrupt(message),
+      Some(rusqlite::ErrorCode::PermissionDenied | rusqlite::ErrorCode::ReadOnly) => {
+        CommandError::PermissionDenied(message)
+      }
+      _ => CommandError::Database(message),
+    }
+  }
+}
+
+impl From<Vec<FieldError>> for CommandError {
+  fn from(fields: Vec<FieldError>) -> Self {
+    CommandError::Validation { fields }
+  }
+}
+
+fn describe_fields(fields: &[FieldError]) -> String {
+  fields
+    .iter()
+    .map(|field| field.message.as_str())
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+impl Serialize for CommandError {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let fields = match self {
+      CommandError::Validation { fields } => Some(fields),
+      _ => None,
+    };
+
+    let mut state = serializer.serialize_struct("CommandError", if fields.is_some() { 3 } else { 2 })?;
+    state.serialize_field("code", self.code())?;
+    state.serialize_field("message", &self.to_string())?;
+    if let Some(fields) = fields {
+      state.serialize_field("fields", fields)?;
+    }
+    state.end()
+  }
+}
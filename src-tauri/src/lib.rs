@@ -1,72 +1,84 @@
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
-use tauri::Manager;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct PaymentProfile {
-  card_number: String,
-  card_expiration: String,
-  zip_code: String,
-  license: String,
-}
-
-fn payment_profile_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-  let mut path = app
-    .path()
-    .app_data_dir()
-    .map_err(|error| format!("Failed to resolve app data directory: {error}"))?;
-
-  path.push("payment_profile.json");
-  Ok(path)
-}
-
-#[tauri::command]
-fn load_payment_profile(app: tauri::AppHandle) -> Result<Option<PaymentProfile>, String> {
-  let path = payment_profile_path(&app)?;
-  if !path.exists() {
-    return Ok(None);
-  }
-
-  let raw = fs::read_to_string(path)
-    .map_err(|error| format!("Failed to read payment profile file: {error}"))?;
-  let parsed = serde_json::from_str::<PaymentProfile>(&raw)
-    .map_err(|error| format!("Failed to parse payment profile file: {error}"))?;
-
-  Ok(Some(parsed))
-}
-
-#[tauri::command]
-fn save_payment_profile(app: tauri::AppHandle, profile: PaymentProfile) -> Result<(), String> {
-  let path = payment_profile_path(&app)?;
-
-  if let Some(parent) = path.parent() {
-    fs::create_dir_all(parent)
-      .map_err(|error| format!("Failed to create payment profile directory: {error}"))?;
-  }
-
-  let serialized = serde_json::to_string_pretty(&profile)
-    .map_err(|error| format!("Failed to serialize payment profile: {error}"))?;
-
-  fs::write(path, serialized)
-    .map_err(|error| format!("Failed to write payment profile file: {error}"))?;
+mod calendar;
+mod crypto;
+mod deep_link;
+mod diagnostics;
+mod error;
+mod local_data;
+mod logging;
+mod paths;
+mod payment_profile;
+mod rules;
+mod sessions;
+mod storage;
+#[cfg(test)]
+mod test_support;
+mod tray;
+mod trip;
+mod zones;
 
-  Ok(())
-}
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
-    .invoke_handler(tauri::generate_handler![load_payment_profile, save_payment_profile])
+    // Registered first so a second launch exits before it touches any local data. Its `parkos://`
+    // arguments go to the deep link handler of this instance.
+    .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+      tray::show_main_window(app);
+      deep_link::open_urls(app, &deep_link::forwarded_urls(&args));
+    }))
+    .plugin(logging::plugin())
+    .plugin(tauri_plugin_deep_link::init())
+    .plugin(tauri_plugin_notification::init())
+    .invoke_handler(tauri::generate_handler![
+      payment_profile::load_payment_profile,
+      payment_profile::save_payment_profile,
+      payment_profile::get_payment_profile_summary,
+      payment_profile::list_payment_profiles,
+      payment_profile::create_payment_profile,
+      payment_profile::update_payment_profile,
+      payment_profile::remove_payment_profile,
+      payment_profile::set_default_payment_profile,
+      payment_profile::create_vehicle,
+      payment_profile::update_vehicle,
+      payment_profile::remove_vehicle,
+      payment_profile::delete_payment_profile,
+      local_data::wipe_local_data,
+      diagnostics::export_diagnostics,
+      zones::lookup_current_zone,
+      zones::recommend_parking,
+      rules::estimate_parking_cost,
+      calendar::is_enforced,
+      trip::parse_trip_prompt,
+      trip::search_destinations,
+      sessions::capture_parking_session,
+      sessions::activate_parking_session,
+      sessions::renew_parking_session,
+      sessions::cancel_parking_session,
+      sessions::list_parking_sessions,
+      sessions::resume_parking_session,
+    ])
+    .on_window_event(|window, event| {
+      // Closing the window leaves the app running in the tray; "Quit" in the tray menu exits.
+      if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+        api.prevent_close();
+        if let Err(error) = window.hide() {
+          log::warn!("Failed to hide window: {error}");
+        }
+      }
+    })
     .setup(|app| {
-      if cfg!(debug_assertions) {
-        app.handle().plugin(
-          tauri_plugin_log::Builder::default()
-            .level(log::LevelFilter::Info)
-            .build(),
-        )?;
+      if let Some(root) = paths::data_root() {
+        log::info!("Storing app data under {}", root.display());
       }
+      app.manage(zones::ZoneIndex::load_bundled()?);
+      app.manage(calendar::EnforcementCalendar::load_bundled()?);
+      app.manage(trip::Gazetteer::load_bundled()?);
+      app.manage(diagnostics::LookupHistory::default());
+      app.manage(sessions::SessionStore::new(app.handle())?);
+      tray::create(app.handle())?;
+      sessions::start_reminders(app.handle().clone())?;
+      deep_link::listen(app.handle());
       Ok(())
     })
     .run(tauri::generate_context!())
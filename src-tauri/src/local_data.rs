@@ -0,0 +1,22 @@
+use crate::error::{CommandError, CommandResult};
+use crate::sessions::SessionStore;
+use crate::{paths, storage};
+
+/// Erases everything the app keeps on this machine, for signing out or handing the device over.
+#[tauri::command]
+pub fn wipe_local_data(app: tauri::AppHandle, sessions: tauri::State<'_, SessionStore>) -> CommandResult<()> {
+  // Held until the directories are gone, so the reminder thread cannot recreate the database.
+  let _suspended = sessions.suspend();
+
+  let data_dir = paths::app_data_dir(&app)?;
+  let local_data_dir = paths::app_local_data_dir(&app)?;
+
+  storage::shred_dir(&data_dir).map_err(|error| CommandError::io("Failed to wipe app data directory", error))?;
+  if local_data_dir != data_dir {
+    storage::shred_dir(&local_data_dir)
+      .map_err(|error| CommandError::io("Failed to wipe app local data directory", error))?;
+  }
+
+  log::info!("Wiped local app data");
+  Ok(())
+}
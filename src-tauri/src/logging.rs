@@ -0,0 +1,209 @@
+//! Logging for every build: JSON lines on stdout and in rotated files in the app log dir (or
+//! `logs/` under the data directory override, see `paths::data_root`). Each message goes through
+//! `redact` first, so payment and contact details never reach a log line.
+
+use crate::paths;
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+
+/// Overrides the default level, e.g. `PARKOS_LOG=debug`.
+const LOG_LEVEL_ENV: &str = "PARKOS_LOG";
+const DEFAULT_LEVEL: log::LevelFilter = log::LevelFilter::Info;
+const LOG_FILE_NAME: &str = "parkos";
+const MAX_LOG_FILE_BYTES: u128 = 2 * 1024 * 1024;
+const KEPT_LOG_FILES: usize = 5;
+const REDACTED: &str = "[redacted]";
+
+pub fn plugin<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+  tauri_plugin_log::Builder::new()
+    .clear_targets()
+    .targets([
+      Target::new(TargetKind::Stdout),
+      Target::new(file_target()),
+    ])
+    .rotation_strategy(RotationStrategy::KeepSome(KEPT_LOG_FILES))
+    .max_file_size(MAX_LOG_FILE_BYTES)
+    .level(level())
+    .format(|out, message, record| {
+      let line = serde_json::json!({
+        "time": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": redact(&message.to_string()),
+      });
+      out.finish(format_args!("{line}"))
+    })
+    .build()
+}
+
+fn file_target() -> TargetKind {
+  let file_name = Some(LOG_FILE_NAME.to_string());
+  match paths::log_dir_override() {
+    Some(path) => TargetKind::Folder { path, file_name },
+    None => TargetKind::LogDir { file_name },
+  }
+}
+
+fn level() -> log::LevelFilter {
+  std::env::var(LOG_LEVEL_ENV)
+    .ok()
+    .and_then(|level| level.trim().parse().ok())
+    .unwrap_or(DEFAULT_LEVEL)
+}
+
+struct Patterns {
+  /// `cardNumber=…`, `"zip": "…"`, `token=…` and friends, whatever the value looks like.
+  keyed: Regex,
+  /// Resume tokens and other long random strings.
+  token: Regex,
+  card: Regex,
+  phone: Regex,
+  expiry: Regex,
+  /// ZIP+4, or five digits after a state like `CA 93401`. A bare five-digit number is left alone,
+  /// since zone numbers look the same; `zip=…` fields are caught by `keyed`.
+  zip: Regex,
+  /// Two or more letters and digits each, like `7ABC123`.
+  plate: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+  static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+  PATTERNS.get_or_init(|| {
+    let compile = |pattern: &str| Regex::new(pattern).expect("redaction pattern is valid");
+    Patterns {
+      keyed: compile(
+        r#"(?i)\b(card_?number|cvc|cvv|exp(?:iry|iration)?(?:_?(?:date|month|year))?|zip(?:_?code)?|postal_?code|(?:license_?)?plate(?:_?number)?|phone(?:_?number)?|\w*token)("?\s*[:=]\s*)("[^"]*"|[^"&\s,;}]+)"#,
+      ),
+      token: compile(r"[A-Za-z0-9_-]{24,}"),
+      card: compile(r"\b\d(?:[ -]?\d){12,18}\b"),
+      phone: compile(r"(?:\+?\b1[ .-]?)?(?:\(\d{3}\)\s?|\b\d{3}[ .-]?)\d{3}[ .-]?\d{4}\b"),
+      expiry: compile(r"\b(?:0?[1-9]|1[0-2])\s?/\s?(?:\d{4}|\d{2})\b"),
+      zip: compile(r"\b([A-Z]{2}\s+)?(\d{5}(?:-\d{4})?)\b"),
+      plate: compile(r"\b[A-Z0-9]{5,8}\b"),
+    }
+  })
+}
+
+/// Masks card numbers, expirations, ZIPs, plates, phone numbers and resume tokens. Errs on the side
+/// of masking: a false positive costs a log detail, a miss leaks a secret.
+pub fn redact(message: &str) -> String {
+  let patterns = patterns();
+  let message = patterns.keyed.replace_all(message, |captures: &Captures<'_>| {
+    let quote = if captures[3].starts_with('"') { "\"" } else { "" };
+    format!("{}{}{quote}{REDACTED}{quote}", &captures[1], &captures[2])
+  });
+  let message = patterns.token.replace_all(&message, |captures: &Captures<'_>| {
+    let candidate = &captures[0];
+    let is_session_id = candidate.len() == 36 && uuid::Uuid::try_parse(candidate).is_ok();
+    if is_session_id || !has_letter_and_digit(candidate) {
+      candidate.to_string()
+    } else {
+      REDACTED.to_string()
+    }
+  });
+  let message = patterns.card.replace_all(&message, REDACTED);
+  let message = patterns.phone.replace_all(&message, REDACTED);
+  let message = patterns.expiry.replace_all(&message, REDACTED);
+  let message = patterns.zip.replace_all(&message, |captures: &Captures<'_>| match captures.get(1) {
+    Some(state) => format!("{}{REDACTED}", state.as_str()),
+    None if captures[2].len() > 5 => REDACTED.to_string(),
+    None => captures[0].to_string(),
+  });
+  let message = patterns.plate.replace_all(&message, |captures: &Captures<'_>| {
+    let candidate = &captures[0];
+    let letters = candidate.chars().filter(char::is_ascii_alphabetic).count();
+    if letters >= 2 && candidate.len() - letters >= 2 {
+      REDACTED.to_string()
+    } else {
+      candidate.to_string()
+    }
+  });
+  message.into_owned()
+}
+
+fn has_letter_and_digit(value: &str) -> bool {
+  value.chars().any(|character| character.is_ascii_alphabetic())
+    && value.chars().any(|character| character.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assert_redacts(cases: &[(&str, &str)]) {
+    for (message, expected) in cases {
+      assert_eq!(redact(message), *expected, "{message}");
+    }
+  }
+
+  #[test]
+  fn card_numbers() {
+    assert_redacts(&[
+      ("card 4242424242424242 declined", "card [redacted] declined"),
+      ("card 4242 4242 4242 4242 declined", "card [redacted] declined"),
+      ("card 4242-4242-4242-4242 declined", "card [redacted] declined"),
+      ("card 3782 822463 10005 declined", "card [redacted] declined"),
+      ("cardNumber=4242424242424242&zone=80511", "cardNumber=[redacted]&zone=80511"),
+    ]);
+  }
+
+  #[test]
+  fn expirations() {
+    assert_redacts(&[
+      ("expires 12/28", "expires [redacted]"),
+      ("expires 3 / 2031", "expires [redacted]"),
+      (r#"{"expMonth": "12", "expYear": "2028"}"#, r#"{"expMonth": "[redacted]", "expYear": "[redacted]"}"#),
+    ]);
+  }
+
+  #[test]
+  fn zip_codes() {
+    assert_redacts(&[
+      ("San Luis Obispo, CA 93401", "San Luis Obispo, CA [redacted]"),
+      ("mail to 93401-1234", "mail to [redacted]"),
+      ("zip=93401", "zip=[redacted]"),
+      (r#"{"zipCode": "93401"}"#, r#"{"zipCode": "[redacted]"}"#),
+    ]);
+  }
+
+  #[test]
+  fn plates() {
+    assert_redacts(&[
+      ("plate 7ABC123 saved", "plate [redacted] saved"),
+      ("licensePlate=ABC1234", "licensePlate=[redacted]"),
+      (r#"{"plate": "PARK N"}"#, r#"{"plate": "[redacted]"}"#),
+    ]);
+  }
+
+  #[test]
+  fn phone_numbers() {
+    assert_redacts(&[
+      ("texting (805) 555-0134", "texting [redacted]"),
+      ("texting 805-555-0134", "texting [redacted]"),
+      ("texting +1 805 555 0134", "texting [redacted]"),
+      ("texting 8055550134", "texting [redacted]"),
+    ]);
+  }
+
+  #[test]
+  fn resume_links() {
+    assert_redacts(&[
+      ("Opening parkos://resume?token=3f2a9c7e1b4d", "Opening parkos://resume?token=[redacted]"),
+      ("Opening /parking?resume=3f2a9c7e1b4d5a6f7e8d9c0b1a2f3e4d", "Opening /parking?resume=[redacted]"),
+    ]);
+  }
+
+  #[test]
+  fn keeps_zone_and_session_ids() {
+    assert_redacts(&[
+      ("Resolved zone 80511 at $2.00/hr", "Resolved zone 80511 at $2.00/hr"),
+      ("zone=80511", "zone=80511"),
+      (
+        "Resuming parking session 0b6f3c7e-4a2d-4f1e-9c8b-7d6e5f4a3b2c from link",
+        "Resuming parking session 0b6f3c7e-4a2d-4f1e-9c8b-7d6e5f4a3b2c from link",
+      ),
+      ("Queued 3 reminders for 12:45 PM", "Queued 3 reminders for 12:45 PM"),
+    ]);
+  }
+}
@@ -0,0 +1,173 @@
+use crate::error::{CommandError, CommandResult};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::Manager;
+
+/// `--data-dir <path>` or `--data-dir=<path>` moves every file the app stores under `<path>`.
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+/// Same as `--data-dir`, for launchers that cannot pass arguments. The flag wins if both are set.
+const DATA_DIR_ENV: &str = "PARKOS_DATA_DIR";
+/// Portable mode: with this file next to the executable, data lives in `PORTABLE_DATA_DIR` beside it.
+const PORTABLE_MARKER: &str = "parkos.portable";
+const PORTABLE_DATA_DIR: &str = "parkos-data";
+
+/// Directory that replaces the OS defaults, if the flag, the environment or portable mode set one.
+/// Resolved once, so every file agrees for the life of the process.
+pub fn data_root() -> Option<&'static Path> {
+  static DATA_ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+  DATA_ROOT.get_or_init(resolve_data_root).as_deref()
+}
+
+fn resolve_data_root() -> Option<PathBuf> {
+  choose_data_root(
+    std::env::args().skip(1),
+    std::env::var_os(DATA_DIR_ENV),
+    std::env::current_dir().ok().as_deref(),
+    std::env::current_exe().ok().as_deref(),
+  )
+}
+
+/// `resolve_data_root` with the process arguments, `PARKOS_DATA_DIR`, working directory and
+/// executable path passed in.
+fn choose_data_root(
+  args: impl Iterator<Item = String>,
+  env_value: Option<OsString>,
+  current_dir: Option<&Path>,
+  executable: Option<&Path>,
+) -> Option<PathBuf> {
+  let configured = data_dir_flag(args).or_else(|| env_value.filter(|value| !value.is_empty()).map(PathBuf::from));
+
+  match configured {
+    Some(path) if path.is_relative() => current_dir.map(|dir| dir.join(path)),
+    Some(path) => Some(path),
+    None => executable.and_then(portable_data_root),
+  }
+}
+
+fn data_dir_flag(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+  while let Some(arg) = args.next() {
+    if arg == DATA_DIR_FLAG {
+      return args.next().filter(|value| !value.is_empty()).map(PathBuf::from);
+    }
+    if let Some(value) = arg.strip_prefix(DATA_DIR_FLAG).and_then(|rest| rest.strip_prefix('=')) {
+      return (!value.is_empty()).then(|| PathBuf::from(value));
+    }
+  }
+  None
+}
+
+fn portable_data_root(executable: &Path) -> Option<PathBuf> {
+  let dir = executable.parent()?;
+  dir.join(PORTABLE_MARKER).is_file().then(|| dir.join(PORTABLE_DATA_DIR))
+}
+
+/// Log directory under `data_root`, for the log plugin, which is set up before the app exists.
+pub fn log_dir_override() -> Option<PathBuf> {
+  data_root().map(|root| root.join("logs"))
+}
+
+pub fn app_data_dir(app: &tauri::AppHandle) -> CommandResult<PathBuf> {
+  if let Some(root) = data_root() {
+    return Ok(root.join("data"));
+  }
+  app
+    .path()
+    .app_data_dir()
+    .map_err(|error| CommandError::Io(format!("Failed to resolve app data directory: {error}")))
+}
+
+/// Local (non-roaming) counterpart of `app_data_dir` for files that must not be synced.
+pub fn app_local_data_dir(app: &tauri::AppHandle) -> CommandResult<PathBuf> {
+  if let Some(root) = data_root() {
+    return Ok(root.join("local"));
+  }
+  app
+    .path()
+    .app_local_data_dir()
+    .map_err(|error| CommandError::Io(format!("Failed to resolve app local data directory: {error}")))
+}
+
+pub fn app_log_dir(app: &tauri::AppHandle) -> CommandResult<PathBuf> {
+  if let Some(dir) = log_dir_override() {
+    return Ok(dir);
+  }
+  app
+    .path()
+    .app_log_dir()
+    .map_err(|error| CommandError::Io(format!("Failed to resolve app log directory: {error}")))
+}
+
+pub fn app_data_file(app: &tauri::AppHandle, file_name: &str) -> CommandResult<PathBuf> {
+  Ok(app_data_dir(app)?.join(file_name))
+}
+
+pub fn app_local_data_file(app: &tauri::AppHandle, file_name: &str) -> CommandResult<PathBuf> {
+  Ok(app_local_data_dir(app)?.join(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  fn args(values: &[&str]) -> impl Iterator<Item = String> {
+    values.iter().map(|value| value.to_string()).collect::<Vec<_>>().into_iter()
+  }
+
+  #[test]
+  fn data_dir_flag_forms() {
+    let cases: [(&[&str], Option<&str>); 8] = [
+      (&["--data-dir", "/srv/parkos"], Some("/srv/parkos")),
+      (&["--data-dir=/srv/parkos"], Some("/srv/parkos")),
+      (&["--verbose", "--data-dir", "relative/dir", "--other"], Some("relative/dir")),
+      (&["--data-dir", ""], None),
+      (&["--data-dir="], None),
+      (&["--data-dir"], None),
+      (&["--data-directory=/srv/parkos"], None),
+      (&[], None),
+    ];
+
+    for (values, expected) in cases {
+      assert_eq!(data_dir_flag(args(values)), expected.map(PathBuf::from), "{values:?}");
+    }
+  }
+
+  #[test]
+  fn data_root_precedence() {
+    let dir = TempDir::new();
+    let flag = dir.join("flag");
+    let env = dir.join("env");
+    let cwd = dir.join("cwd");
+    let flag_arg = format!("--data-dir={}", flag.display());
+    let cases: [(&[&str], Option<&Path>, Option<PathBuf>); 6] = [
+      (&[flag_arg.as_str()], Some(&env), Some(flag.clone())),
+      (&[], Some(&env), Some(env.clone())),
+      (&["--data-dir", "relative"], None, Some(cwd.join("relative"))),
+      (&[], Some(Path::new("relative")), Some(cwd.join("relative"))),
+      (&["--data-dir="], Some(&env), Some(env.clone())),
+      (&[], Some(Path::new("")), None),
+    ];
+
+    for (values, env_value, expected) in cases {
+      let env_value = env_value.map(OsString::from);
+      let resolved = choose_data_root(args(values), env_value.clone(), Some(&cwd), None);
+      assert_eq!(resolved, expected, "{values:?} {env_value:?}");
+    }
+  }
+
+  #[test]
+  fn portable_marker_puts_data_beside_the_executable() {
+    let dir = TempDir::new();
+    let executable = dir.join("parkos");
+
+    assert_eq!(choose_data_root(args(&[]), None, None, Some(&executable)), None);
+
+    std::fs::write(dir.join(PORTABLE_MARKER), b"").unwrap();
+    assert_eq!(choose_data_root(args(&[]), None, None, Some(&executable)), Some(dir.join(PORTABLE_DATA_DIR)));
+
+    let flag = dir.join("elsewhere");
+    let flag_args = ["--data-dir".to_string(), flag.display().to_string()];
+    assert_eq!(choose_data_root(flag_args.into_iter(), None, None, Some(&executable)), Some(flag));
+  }
+}
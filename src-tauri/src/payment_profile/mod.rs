@@ -0,0 +1,355 @@
+mod schema;
+mod validation;
+mod wallet;
+
+use crate::error::{CommandError, CommandResult};
+use crate::{crypto, paths, storage};
+use std::fs;
+use std::path::{Path, PathBuf};
+pub use validation::FieldError;
+pub use wallet::{PaymentProfile, ProfileInput, ProfileSummary, Vehicle, VehicleInput, Wallet, WalletSummary};
+
+/// The files one wallet is stored in.
+struct ProfileFiles {
+  path: PathBuf,
+  /// Plaintext file written by earlier builds; migrated to the encrypted file on first load.
+  legacy_path: PathBuf,
+  /// The key lives in the local (non-roaming) data dir so it is not synced alongside the ciphertext.
+  key_path: PathBuf,
+}
+
+impl ProfileFiles {
+  fn resolve(app: &tauri::AppHandle) -> CommandResult<Self> {
+    Ok(ProfileFiles {
+      path: paths::app_data_file(app, "payment_profile.enc")?,
+      legacy_path: paths::app_data_file(app, "payment_profile.json")?,
+      key_path: paths::app_local_data_file(app, "payment_profile.key")?,
+    })
+  }
+}
+
+/// Every file the store writes next to the encrypted profile: `.bak`/`.tmp` siblings, the
+/// pre-migration `.vN.bak` copies and the legacy plaintext file.
+fn payment_profile_files(app: &tauri::AppHandle) -> CommandResult<Vec<PathBuf>> {
+  let dir = paths::app_data_dir(app)?;
+  let entries = match fs::read_dir(&dir) {
+    Ok(entries) => entries,
+    Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(error) => return Err(CommandError::io("Failed to list app data directory", error)),
+  };
+
+  let mut files = Vec::new();
+  for entry in entries {
+    let entry = entry.map_err(|error| CommandError::io("Failed to list app data directory", error))?;
+    if entry.file_name().to_string_lossy().starts_with("payment_profile.") {
+      files.push(entry.path());
+    }
+  }
+  Ok(files)
+}
+
+fn write_wallet(files: &ProfileFiles, wallet: &Wallet) -> CommandResult<()> {
+  if let Some(parent) = files.path.parent() {
+    fs::create_dir_all(parent)
+      .map_err(|error| CommandError::io("Failed to create payment profile directory", error))?;
+  }
+
+  let serialized = schema::encode(wallet)?;
+  let key = crypto::load_or_create_key(&files.key_path)?;
+  let sealed = crypto::encrypt(&key, &serialized)?;
+
+  storage::write_atomic(&files.path, &sealed)
+    .map_err(|error| CommandError::io("Failed to write payment profile file", error))?;
+
+  // The plaintext file holds the full card number; overwrite it rather than just unlinking it.
+  storage::shred_file(&files.legacy_path)
+    .map_err(|error| CommandError::io("Failed to remove plaintext payment profile file", error))?;
+
+  Ok(())
+}
+
+fn migrate_legacy_payment_profile(files: &ProfileFiles) -> CommandResult<Wallet> {
+  if !files.legacy_path.exists() {
+    return Ok(Wallet::default());
+  }
+
+  let raw =
+    fs::read(&files.legacy_path).map_err(|error| CommandError::io("Failed to read payment profile file", error))?;
+  let decoded = schema::decode(&raw)?;
+
+  write_wallet(files, &decoded.wallet)?;
+  Ok(decoded.wallet)
+}
+
+/// Keeps the still-encrypted file as it was before an in-place schema upgrade.
+fn backup_before_migration(path: &Path, from_version: u32) -> CommandResult<()> {
+  let backup_path = path.with_extension(format!("v{from_version}.bak"));
+  fs::copy(path, backup_path)
+    .map_err(|error| CommandError::io("Failed to back up payment profile before migration", error))?;
+
+  Ok(())
+}
+
+fn read_payment_profile_file(files: &ProfileFiles, path: &Path) -> CommandResult<schema::Decoded> {
+  let sealed = fs::read(path).map_err(|error| CommandError::io("Failed to read payment profile file", error))?;
+  let key = crypto::load_key(&files.key_path)?;
+  let raw = crypto::decrypt(&key, &sealed)?;
+  schema::decode(&raw)
+}
+
+/// Used when the primary file is truncated or otherwise unreadable; restores the backup on success
+/// so the next save does not rotate the corrupt file into `.bak`.
+fn recover_payment_profile_from_backup(
+  files: &ProfileFiles,
+  primary_error: CommandError,
+) -> CommandResult<schema::Decoded> {
+  let backup_path = storage::backup_path(&files.path);
+  if !backup_path.exists() {
+    return Err(primary_error);
+  }
+
+  let decoded = match read_payment_profile_file(files, &backup_path) {
+    Ok(decoded) => decoded,
+    Err(backup_error) => {
+      log::warn!("Payment profile backup is also unreadable: {backup_error}");
+      return Err(primary_error);
+    }
+  };
+
+  log::warn!("Payment profile file is unreadable ({primary_error}); recovered the last good copy from backup");
+  storage::restore_backup(&files.path)
+    .map_err(|error| CommandError::io("Failed to restore payment profile from backup", error))?;
+
+  Ok(decoded)
+}
+
+fn load_wallet(files: &ProfileFiles) -> CommandResult<Wallet> {
+  if !files.path.exists() {
+    return migrate_legacy_payment_profile(files);
+  }
+
+  let decoded = match read_payment_profile_file(files, &files.path) {
+    Ok(decoded) => decoded,
+    Err(error) => recover_payment_profile_from_backup(files, error)?,
+  };
+
+  if let Some(from_version) = decoded.migrated_from {
+    backup_before_migration(&files.path, from_version)?;
+    write_wallet(files, &decoded.wallet)?;
+    log::info!(
+      "Migrated payment profile from schema version {from_version} to {}",
+      schema::CURRENT_SCHEMA_VERSION
+    );
+  }
+
+  Ok(decoded.wallet)
+}
+
+/// Loads the wallet, applies `change`, and persists the result only if `change` succeeded.
+fn update_wallet<T>(
+  app: &tauri::AppHandle,
+  change: impl FnOnce(&mut Wallet) -> CommandResult<T>,
+) -> CommandResult<T> {
+  let files = ProfileFiles::resolve(app)?;
+  let mut wallet = load_wallet(&files)?;
+  let result = change(&mut wallet)?;
+  write_wallet(&files, &wallet)?;
+  Ok(result)
+}
+
+/// Full card details. Only windows granted `allow-load-payment-profile` may call this; the main
+/// window uses `get_payment_profile_summary`.
+#[tauri::command]
+pub fn load_payment_profile(app: tauri::AppHandle) -> CommandResult<Option<PaymentProfile>> {
+  Ok(load_wallet(&ProfileFiles::resolve(&app)?)?.default_payment_profile())
+}
+
+#[tauri::command]
+pub fn save_payment_profile(app: tauri::AppHandle, profile: PaymentProfile) -> CommandResult<()> {
+  let profile = validation::validate_payment_profile(profile)?;
+  update_wallet(&app, |wallet| {
+    wallet.save_default_payment_profile(profile);
+    Ok(())
+  })
+}
+
+#[tauri::command]
+pub fn get_payment_profile_summary(app: tauri::AppHandle) -> CommandResult<Option<ProfileSummary>> {
+  let wallet = load_wallet(&ProfileFiles::resolve(&app)?)?;
+  Ok(wallet.default_profile().map(|profile| wallet.summarize_profile(profile)))
+}
+
+#[tauri::command]
+pub fn list_payment_profiles(app: tauri::AppHandle) -> CommandResult<WalletSummary> {
+  Ok(load_wallet(&ProfileFiles::resolve(&app)?)?.summary())
+}
+
+#[tauri::command]
+pub fn create_payment_profile(app: tauri::AppHandle, profile: ProfileInput) -> CommandResult<ProfileSummary> {
+  let profile = validation::validate_profile_input(profile)?;
+  update_wallet(&app, |wallet| {
+    let created = wallet.create_profile(profile)?;
+    Ok(wallet.summarize_profile(&created))
+  })
+}
+
+#[tauri::command]
+pub fn update_payment_profile(
+  app: tauri::AppHandle,
+  id: String,
+  profile: ProfileInput,
+) -> CommandResult<ProfileSummary> {
+  let profile = validation::validate_profile_input(profile)?;
+  update_wallet(&app, |wallet| {
+    let updated = wallet.update_profile(&id, profile)?;
+    Ok(wallet.summarize_profile(&updated))
+  })
+}
+
+#[tauri::command]
+pub fn remove_payment_profile(app: tauri::AppHandle, id: String) -> CommandResult<()> {
+  update_wallet(&app, |wallet| wallet.remove_profile(&id))
+}
+
+#[tauri::command]
+pub fn set_default_payment_profile(app: tauri::AppHandle, id: String) -> CommandResult<()> {
+  update_wallet(&app, |wallet| wallet.set_default_profile(&id))
+}
+
+#[tauri::command]
+pub fn create_vehicle(app: tauri::AppHandle, vehicle: VehicleInput) -> CommandResult<Vehicle> {
+  let vehicle = validation::validate_vehicle_input(vehicle)?;
+  update_wallet(&app, |wallet| Ok(wallet.create_vehicle(vehicle)))
+}
+
+#[tauri::command]
+pub fn update_vehicle(app: tauri::AppHandle, id: String, vehicle: VehicleInput) -> CommandResult<Vehicle> {
+  let vehicle = validation::validate_vehicle_input(vehicle)?;
+  update_wallet(&app, |wallet| wallet.update_vehicle(&id, vehicle))
+}
+
+#[tauri::command]
+pub fn remove_vehicle(app: tauri::AppHandle, id: String) -> CommandResult<()> {
+  update_wallet(&app, |wallet| wallet.remove_vehicle(&id))
+}
+
+/// Removes every stored profile and vehicle, including backups and the encryption key.
+#[tauri::command]
+pub fn delete_payment_profile(app: tauri::AppHandle) -> CommandResult<()> {
+  for path in payment_profile_files(&app)? {
+    storage::shred_file(&path).map_err(|error| CommandError::io("Failed to delete payment profile file", error))?;
+  }
+
+  storage::shred_file(&ProfileFiles::resolve(&app)?.key_path)
+    .map_err(|error| CommandError::io("Failed to delete encryption key file", error))?;
+
+  log::info!("Deleted stored payment profiles");
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  fn profile_files(dir: &TempDir) -> ProfileFiles {
+    ProfileFiles {
+      path: dir.join("payment_profile.enc"),
+      legacy_path: dir.join("payment_profile.json"),
+      key_path: dir.join("payment_profile.key"),
+    }
+  }
+
+  fn wallet_with_plate(plate: &str) -> Wallet {
+    let mut wallet = Wallet::default();
+    wallet.create_vehicle(VehicleInput {
+      plate: plate.to_string(),
+      plate_state: Some("CA".to_string()),
+      make: "Honda".to_string(),
+      model: "Civic".to_string(),
+      color: None,
+    });
+    wallet
+  }
+
+  fn plates(wallet: &Wallet) -> Vec<&str> {
+    wallet.vehicles.iter().map(|vehicle| vehicle.plate.as_str()).collect()
+  }
+
+  #[test]
+  fn load_wallet_reads_what_write_wallet_wrote() {
+    let dir = TempDir::new();
+    let files = profile_files(&dir);
+
+    write_wallet(&files, &wallet_with_plate("7ABC123")).unwrap();
+
+    assert_eq!(plates(&load_wallet(&files).unwrap()), ["7ABC123"]);
+  }
+
+  #[test]
+  fn load_wallet_recovers_a_truncated_file_from_the_backup() {
+    let dir = TempDir::new();
+    let files = profile_files(&dir);
+    write_wallet(&files, &wallet_with_plate("7ABC123")).unwrap();
+    write_wallet(&files, &wallet_with_plate("8XYZ987")).unwrap();
+    let sealed = fs::read(&files.path).unwrap();
+    fs::write(&files.path, &sealed[..sealed.len() / 2]).unwrap();
+
+    assert_eq!(plates(&load_wallet(&files).unwrap()), ["7ABC123"]);
+    // The backup was put back in place, so a second load reads the primary without recovering.
+    assert_eq!(fs::read(&files.path).unwrap(), fs::read(storage::backup_path(&files.path)).unwrap());
+  }
+
+  #[test]
+  fn load_wallet_reports_corruption_without_a_backup() {
+    let dir = TempDir::new();
+    let files = profile_files(&dir);
+    write_wallet(&files, &wallet_with_plate("7ABC123")).unwrap();
+    let sealed = fs::read(&files.path).unwrap();
+    fs::write(&files.path, &sealed[..sealed.len() / 2]).unwrap();
+
+    assert!(load_wallet(&files).is_err());
+  }
+
+  #[test]
+  fn load_wallet_migrates_and_shreds_the_legacy_plaintext_file() {
+    let dir = TempDir::new();
+    let files = profile_files(&dir);
+    let legacy = r#"{"cardNumber":"4111111111111111","cardExpiration":"12/30","zipCode":"93401","license":"7ABC123"}"#;
+    fs::write(&files.legacy_path, legacy).unwrap();
+
+    let wallet = load_wallet(&files).unwrap();
+
+    assert_eq!(plates(&wallet), ["7ABC123"]);
+    assert!(!files.legacy_path.exists());
+    assert_eq!(plates(&load_wallet(&files).unwrap()), ["7ABC123"]);
+  }
+
+  #[test]
+  fn backup_before_migration_keeps_a_versioned_copy() {
+    let dir = TempDir::new();
+    let path = dir.join("payment_profile.enc");
+    fs::write(&path, b"sealed v2").unwrap();
+
+    backup_before_migration(&path, 2).unwrap();
+
+    assert_eq!(fs::read(dir.join("payment_profile.v2.bak")).unwrap(), b"sealed v2");
+    assert_eq!(fs::read(&path).unwrap(), b"sealed v2");
+  }
+
+  #[test]
+  fn load_wallet_backs_up_an_older_schema_before_rewriting_it() {
+    let dir = TempDir::new();
+    let files = profile_files(&dir);
+    let key = crypto::load_or_create_key(&files.key_path).unwrap();
+    let v2 = r#"{"schemaVersion":2,"profile":{"cardNumber":"4111111111111111","license":"7ABC123"}}"#;
+    let sealed = crypto::encrypt(&key, v2.as_bytes()).unwrap();
+    fs::write(&files.path, &sealed).unwrap();
+
+    assert_eq!(plates(&load_wallet(&files).unwrap()), ["7ABC123"]);
+
+    assert_eq!(fs::read(dir.join("payment_profile.v2.bak")).unwrap(), sealed);
+    let rewritten = read_payment_profile_file(&files, &files.path).unwrap();
+    assert_eq!(rewritten.migrated_from, None);
+  }
+}
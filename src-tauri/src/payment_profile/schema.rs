@@ -0,0 +1,190 @@
+use super::wallet::{new_id, Wallet};
+use crate::error::{CommandError, CommandResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Version written by this build. Bump it and append to `MIGRATIONS` whenever the stored shape changes.
+pub const CURRENT_SCHEMA_VERSION: u32 = 3;
+
+type Migration = fn(Value) -> Result<Value, String>;
+
+/// `MIGRATIONS[n]` upgrades a document from schema version `n + 1` to `n + 2`.
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2, migrate_v2_to_v3];
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope<T> {
+  schema_version: u32,
+  #[serde(flatten)]
+  wallet: T,
+}
+
+pub struct Decoded {
+  pub wallet: Wallet,
+  /// Set when the stored document was older than `CURRENT_SCHEMA_VERSION` and had to be upgraded.
+  pub migrated_from: Option<u32>,
+}
+
+pub fn encode(wallet: &Wallet) -> CommandResult<Vec<u8>> {
+  let envelope = Envelope {
+    schema_version: CURRENT_SCHEMA_VERSION,
+    wallet,
+  };
+
+  serde_json::to_vec(&envelope)
+    .map_err(|error| CommandError::Internal(format!("Failed to serialize payment profile: {error}")))
+}
+
+pub fn decode(raw: &[u8]) -> CommandResult<Decoded> {
+  let document = serde_json::from_slice::<Value>(raw)
+    .map_err(|error| CommandError::Corrupt(format!("Failed to parse payment profile file: {error}")))?;
+  let stored_version = schema_version_of(&document)?;
+
+  if stored_version > CURRENT_SCHEMA_VERSION {
+    return Err(CommandError::Unsupported(format!(
+      "Payment profile file uses schema version {stored_version}, which is newer than this app supports ({CURRENT_SCHEMA_VERSION})"
+    )));
+  }
+
+  let mut document = document;
+  for version in stored_version..CURRENT_SCHEMA_VERSION {
+    document = MIGRATIONS[(version - 1) as usize](document).map_err(|error| {
+      CommandError::Corrupt(format!("Failed to migrate payment profile from schema version {version}: {error}"))
+    })?;
+  }
+
+  let envelope = serde_json::from_value::<Envelope<Wallet>>(document)
+    .map_err(|error| CommandError::Corrupt(format!("Failed to parse payment profile file: {error}")))?;
+
+  Ok(Decoded {
+    wallet: envelope.wallet,
+    migrated_from: (stored_version < CURRENT_SCHEMA_VERSION).then_some(stored_version),
+  })
+}
+
+/// Version 1 files predate the envelope and are a bare profile object.
+fn schema_version_of(document: &Value) -> CommandResult<u32> {
+  match document.get("schemaVersion") {
+    None => Ok(1),
+    Some(value) => value
+      .as_u64()
+      .and_then(|version| u32::try_from(version).ok())
+      .filter(|version| *version >= 1)
+      .ok_or_else(|| CommandError::Corrupt("Payment profile file has an invalid schemaVersion".to_string())),
+  }
+}
+
+fn migrate_v1_to_v2(document: Value) -> Result<Value, String> {
+  if !document.is_object() {
+    return Err("expected a profile object".to_string());
+  }
+
+  Ok(json!({ "schemaVersion": 2, "profile": document }))
+}
+
+/// Version 3 replaced the single profile with lists of profiles and vehicles; the old profile
+/// becomes the default one and its `license` becomes a vehicle with only a plate.
+fn migrate_v2_to_v3(document: Value) -> Result<Value, String> {
+  let profile = document
+    .get("profile")
+    .and_then(Value::as_object)
+    .ok_or_else(|| "expected a profile object".to_string())?;
+  let field = |name: &str| profile.get(name).cloned().unwrap_or_else(|| json!(""));
+
+  let profile_id = new_id();
+  let vehicle_id = new_id();
+
+  Ok(json!({
+    "schemaVersion": 3,
+    "defaultProfileId": profile_id,
+    "profiles": [{
+      "id": profile_id,
+      "label": "Default",
+      "cardNumber": field("cardNumber"),
+      "cardExpiration": field("cardExpiration"),
+      "zipCode": field("zipCode"),
+      "vehicleId": vehicle_id,
+    }],
+    "vehicles": [{
+      "id": vehicle_id,
+      "plate": field("license"),
+      "plateState": null,
+      "make": "",
+      "model": "",
+      "color": null,
+    }],
+  }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const V1_PROFILE: &str =
+    r#"{"cardNumber":"4111111111111111","cardExpiration":"12/30","zipCode":"93401","license":"7ABC123"}"#;
+
+  #[test]
+  fn decode_upgrades_a_bare_v1_profile_to_the_current_wallet() {
+    let decoded = decode(V1_PROFILE.as_bytes()).unwrap();
+
+    assert_eq!(decoded.migrated_from, Some(1));
+    let wallet = decoded.wallet;
+    assert_eq!(wallet.profiles.len(), 1);
+    assert_eq!(wallet.vehicles.len(), 1);
+    let profile = &wallet.profiles[0];
+    assert_eq!(wallet.default_profile_id.as_deref(), Some(profile.id.as_str()));
+    assert_eq!(profile.card_number, "4111111111111111");
+    assert_eq!(profile.card_expiration, "12/30");
+    assert_eq!(profile.zip_code, "93401");
+    assert_eq!(profile.vehicle_id.as_deref(), Some(wallet.vehicles[0].id.as_str()));
+    assert_eq!(wallet.vehicles[0].plate, "7ABC123");
+  }
+
+  #[test]
+  fn decode_upgrades_a_v2_envelope() {
+    let raw = format!(r#"{{"schemaVersion":2,"profile":{V1_PROFILE}}}"#);
+
+    let decoded = decode(raw.as_bytes()).unwrap();
+
+    assert_eq!(decoded.migrated_from, Some(2));
+    assert_eq!(decoded.wallet.profiles[0].card_number, "4111111111111111");
+    assert_eq!(decoded.wallet.vehicles[0].plate, "7ABC123");
+  }
+
+  #[test]
+  fn decode_reads_the_current_version_without_migrating() {
+    let mut wallet = decode(V1_PROFILE.as_bytes()).unwrap().wallet;
+    wallet.profiles[0].label = "Work".to_string();
+
+    let decoded = decode(&encode(&wallet).unwrap()).unwrap();
+
+    assert_eq!(decoded.migrated_from, None);
+    assert_eq!(decoded.wallet.profiles[0].label, "Work");
+    assert_eq!(decoded.wallet.default_profile_id, wallet.default_profile_id);
+  }
+
+  #[test]
+  fn decode_rejects_a_newer_schema_version() {
+    let raw = format!(r#"{{"schemaVersion":{},"profiles":[],"vehicles":[]}}"#, CURRENT_SCHEMA_VERSION + 1);
+
+    assert!(matches!(decode(raw.as_bytes()), Err(CommandError::Unsupported(_))));
+  }
+
+  #[test]
+  fn decode_reports_invalid_documents_as_corrupt() {
+    let cases = [
+      "not json",
+      r#"{"schemaVersion":"3","profiles":[],"vehicles":[]}"#,
+      r#"{"schemaVersion":0,"profiles":[],"vehicles":[]}"#,
+      r#"{"schemaVersion":-1}"#,
+      r#"{"schemaVersion":2.5}"#,
+      r#"{"schemaVersion":null}"#,
+      r#"{"schemaVersion":2,"profile":"7ABC123"}"#,
+      r#"["7ABC123"]"#,
+    ];
+
+    for raw in cases {
+      assert!(matches!(decode(raw.as_bytes()), Err(CommandError::Corrupt(_))), "{raw}");
+    }
+  }
+}
@@ -0,0 +1,464 @@
+use super::wallet::{PaymentProfile, ProfileInput, VehicleInput};
+use chrono::Datelike;
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+/// One rejected form field; `field` uses the camelCase name the webview sends.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+  pub field: String,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CardBrand {
+  Visa,
+  Mastercard,
+  Amex,
+  Discover,
+  DinersClub,
+  Jcb,
+  Unknown,
+}
+
+/// USPS codes for the states, DC and territories that issue plates.
+const PLATE_STATES: &[&str] = &[
+  "AL", "AK", "AZ", "AR", "CA", "CO", "CT", "DE", "DC", "FL", "GA", "HI", "ID", "IL", "IN", "IA", "KS",
+  "KY", "LA", "ME", "MD", "MA", "MI", "MN", "MS", "MO", "MT", "NE", "NV", "NH", "NJ", "NM", "NY", "NC",
+  "ND", "OH", "OK", "OR", "PA", "RI", "SC", "SD", "TN", "TX", "UT", "VT", "VA", "WA", "WV", "WI", "WY",
+  "AS", "GU", "MP", "PR", "VI",
+];
+
+/// What one issuer's plates look like once spaces and dashes are removed. `pattern` must accept
+/// personalized and specialty plates as well as the standard serial, so for most states it is a
+/// character class and length range rather than the serial layout.
+struct PlateFormat {
+  state: &'static str,
+  pattern: &'static str,
+  /// Completes "{state} plates …" in the error shown to the user.
+  rule: &'static str,
+}
+
+const PLATE_FORMATS: &[PlateFormat] = &[
+  PlateFormat {
+    state: "CA",
+    pattern: r"^[A-Z0-9]{2,7}$",
+    rule: "have 2 to 7 letters and numbers, like 8ABC123",
+  },
+  PlateFormat {
+    state: "AZ",
+    pattern: r"^[A-Z0-9]{1,7}$",
+    rule: "have up to 7 letters and numbers, like ABC1234",
+  },
+  PlateFormat {
+    state: "NV",
+    pattern: r"^[A-Z0-9]{1,7}$",
+    rule: "have up to 7 letters and numbers, like 123A456",
+  },
+  PlateFormat {
+    state: "WA",
+    pattern: r"^[A-Z0-9]{1,7}$",
+    rule: "have up to 7 letters and numbers, like ABC1234",
+  },
+  PlateFormat {
+    state: "TX",
+    pattern: r"^[A-Z0-9]{1,7}$",
+    rule: "have up to 7 letters and numbers, like ABC1234",
+  },
+  PlateFormat {
+    state: "FL",
+    pattern: r"^[A-Z0-9]{1,7}$",
+    rule: "have up to 7 letters and numbers, like ABCD12",
+  },
+  PlateFormat {
+    state: "NY",
+    pattern: r"^[A-Z0-9]{1,8}$",
+    rule: "have up to 8 letters and numbers, like ABC1234",
+  },
+];
+
+/// Used when the state is missing or not in `PLATE_FORMATS`; no state issues plates longer than 8.
+const DEFAULT_PLATE_FORMAT: PlateFormat = PlateFormat {
+  state: "US",
+  pattern: r"^[A-Z0-9]{1,8}$",
+  rule: "have at most 8 letters and numbers",
+};
+
+fn plate_format(state: Option<&str>) -> (&'static PlateFormat, &'static Regex) {
+  static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+  let patterns = PATTERNS.get_or_init(|| {
+    PLATE_FORMATS
+      .iter()
+      .chain([&DEFAULT_PLATE_FORMAT])
+      .map(|format| Regex::new(format.pattern).expect("plate pattern is valid"))
+      .collect()
+  });
+
+  let index = state
+    .and_then(|state| PLATE_FORMATS.iter().position(|format| format.state == state))
+    .unwrap_or(PLATE_FORMATS.len());
+  (PLATE_FORMATS.get(index).unwrap_or(&DEFAULT_PLATE_FORMAT), &patterns[index])
+}
+
+#[derive(Default)]
+struct Errors(Vec<FieldError>);
+
+impl Errors {
+  fn check<T>(&mut self, field: &str, result: Result<T, String>) -> Option<T> {
+    result
+      .map_err(|message| {
+        self.0.push(FieldError {
+          field: field.to_string(),
+          message,
+        })
+      })
+      .ok()
+  }
+
+  fn finish<T>(self, value: T) -> Result<T, Vec<FieldError>> {
+    if self.0.is_empty() {
+      Ok(value)
+    } else {
+      Err(self.0)
+    }
+  }
+}
+
+pub fn validate_payment_profile(profile: PaymentProfile) -> Result<PaymentProfile, Vec<FieldError>> {
+  let mut errors = Errors::default();
+  let card_number = errors.check("cardNumber", normalize_card_number(&profile.card_number));
+  let card_expiration = errors.check("cardExpiration", normalize_expiration(&profile.card_expiration));
+  let zip_code = errors.check("zipCode", normalize_zip_code(&profile.zip_code));
+  let license = errors.check("license", normalize_plate(&profile.license, None));
+
+  let normalized = match (card_number, card_expiration, zip_code, license) {
+    (Some(card_number), Some(card_expiration), Some(zip_code), Some(license)) => PaymentProfile {
+      card_number,
+      card_expiration,
+      zip_code,
+      license,
+    },
+    _ => profile,
+  };
+  errors.finish(normalized)
+}
+
+pub fn validate_profile_input(input: ProfileInput) -> Result<ProfileInput, Vec<FieldError>> {
+  let mut errors = Errors::default();
+  let label = errors.check("label", require("Label", &input.label));
+  let card_number = errors.check("cardNumber", normalize_card_number(&input.card_number));
+  let card_expiration = errors.check("cardExpiration", normalize_expiration(&input.card_expiration));
+  let zip_code = errors.check("zipCode", normalize_zip_code(&input.zip_code));
+
+  let normalized = match (label, card_number, card_expiration, zip_code) {
+    (Some(label), Some(card_number), Some(card_expiration), Some(zip_code)) => ProfileInput {
+      label,
+      card_number,
+      card_expiration,
+      zip_code,
+      vehicle_id: input.vehicle_id,
+    },
+    _ => input,
+  };
+  errors.finish(normalized)
+}
+
+pub fn validate_vehicle_input(input: VehicleInput) -> Result<VehicleInput, Vec<FieldError>> {
+  let mut errors = Errors::default();
+  let plate_state = input
+    .plate_state
+    .as_deref()
+    .map(str::trim)
+    .filter(|state| !state.is_empty())
+    .map(normalize_plate_state)
+    .transpose();
+  let plate_state = errors.check("plateState", plate_state);
+  let known_state = plate_state.as_ref().and_then(|state| state.as_deref());
+  let plate = errors.check("plate", normalize_plate(&input.plate, known_state));
+  let make = errors.check("make", require("Make", &input.make));
+  let model = errors.check("model", require("Model", &input.model));
+  let color = input.color.as_deref().map(str::trim).filter(|color| !color.is_empty()).map(String::from);
+
+  let normalized = match (plate, plate_state, make, model) {
+    (Some(plate), Some(plate_state), Some(make), Some(model)) => VehicleInput {
+      plate,
+      plate_state,
+      make,
+      model,
+      color,
+    },
+    _ => input,
+  };
+  errors.finish(normalized)
+}
+
+fn require(label: &str, value: &str) -> Result<String, String> {
+  let trimmed = value.trim();
+  if trimmed.is_empty() {
+    return Err(format!("{label} is required."));
+  }
+  Ok(trimmed.to_string())
+}
+
+/// Strips spaces and dashes, then checks length, the Luhn checksum and that the brand is known.
+pub fn normalize_card_number(value: &str) -> Result<String, String> {
+  let digits: String = value.chars().filter(|c| !c.is_whitespace() && *c != '-').collect();
+  if digits.is_empty() {
+    return Err("Card number is required.".to_string());
+  }
+  if !digits.chars().all(|c| c.is_ascii_digit()) {
+    return Err("Card number may only contain digits.".to_string());
+  }
+  if !(12..=19).contains(&digits.len()) {
+    return Err("Card number must be between 12 and 19 digits.".to_string());
+  }
+  if !passes_luhn(&digits) {
+    return Err("Card number is not valid; check for a typo.".to_string());
+  }
+  if card_brand(&digits) == CardBrand::Unknown {
+    return Err("Card brand is not supported.".to_string());
+  }
+  Ok(digits)
+}
+
+pub fn passes_luhn(digits: &str) -> bool {
+  let sum: u32 = digits
+    .bytes()
+    .rev()
+    .enumerate()
+    .map(|(index, byte)| {
+      let digit = u32::from(byte - b'0');
+      if index % 2 == 1 {
+        let doubled = digit * 2;
+        if doubled > 9 {
+          doubled - 9
+        } else {
+          doubled
+        }
+      } else {
+        digit
+      }
+    })
+    .sum();
+  sum % 10 == 0
+}
+
+/// Brand from the IIN prefix; expects digits only.
+pub fn card_brand(digits: &str) -> CardBrand {
+  let prefix = |len: usize| digits.get(..len).and_then(|p| p.parse::<u32>().ok()).unwrap_or(0);
+  let length = digits.len();
+
+  if digits.starts_with('4') && matches!(length, 13 | 16 | 19) {
+    CardBrand::Visa
+  } else if ((51..=55).contains(&prefix(2)) || (2221..=2720).contains(&prefix(4))) && length == 16 {
+    CardBrand::Mastercard
+  } else if matches!(prefix(2), 34 | 37) && length == 15 {
+    CardBrand::Amex
+  } else if (prefix(4) == 6011 || prefix(2) == 65 || (644..=649).contains(&prefix(3))) && (16..=19).contains(&length) {
+    CardBrand::Discover
+  } else if ((300..=305).contains(&prefix(3)) || matches!(prefix(2), 36 | 38 | 39)) && (14..=19).contains(&length) {
+    CardBrand::DinersClub
+  } else if (3528..=3589).contains(&prefix(4)) && (16..=19).contains(&length) {
+    CardBrand::Jcb
+  } else {
+    CardBrand::Unknown
+  }
+}
+
+/// Accepts `MM/YY` (also `M/YY` and `MM/YYYY`) and rejects cards whose expiry month has passed.
+pub fn normalize_expiration(value: &str) -> Result<String, String> {
+  let (month, year) = parse_expiration(value)?;
+  let today = chrono::Local::now().date_naive();
+  if (year, month) < (today.year(), today.month()) {
+    return Err("Card has expired.".to_string());
+  }
+  Ok(format!("{month:02}/{:02}", year % 100))
+}
+
+/// Returns `(month, four-digit year)`.
+pub fn parse_expiration(value: &str) -> Result<(u32, i32), String> {
+  let invalid = || "Expiration must be in MM/YY format.".to_string();
+  let (month, year) = value.trim().split_once('/').ok_or_else(invalid)?;
+  let (month, year) = (month.trim(), year.trim());
+  if month.is_empty() || month.len() > 2 || !matches!(year.len(), 2 | 4) {
+    return Err(invalid());
+  }
+
+  let month = month.parse::<u32>().map_err(|_| invalid())?;
+  let year = year.parse::<i32>().map_err(|_| invalid())?;
+  if !(1..=12).contains(&month) {
+    return Err("Expiration month must be between 01 and 12.".to_string());
+  }
+  let year = if year < 100 { 2000 + year } else { year };
+  Ok((month, year))
+}
+
+/// US ZIP (`93401`) or ZIP+4 (`93401-1234`).
+pub fn normalize_zip_code(value: &str) -> Result<String, String> {
+  let trimmed = value.trim();
+  let is_digits = |part: &str, len: usize| part.len() == len && part.chars().all(|c| c.is_ascii_digit());
+  let valid = match trimmed.split_once('-') {
+    Some((zip, plus_four)) => is_digits(zip, 5) && is_digits(plus_four, 4),
+    None => is_digits(trimmed, 5),
+  };
+
+  if !valid {
+    return Err("ZIP code must be 5 digits or ZIP+4 (12345-6789).".to_string());
+  }
+  Ok(trimmed.to_string())
+}
+
+pub fn normalize_plate_state(value: &str) -> Result<String, String> {
+  let state = value.trim().to_ascii_uppercase();
+  if !PLATE_STATES.contains(&state.as_str()) {
+    return Err("Plate state must be a two-letter US state code.".to_string());
+  }
+  Ok(state)
+}
+
+/// Uppercases the plate, drops separators, and checks it against the format for `state` (or the
+/// most permissive format when the state is unknown).
+pub fn normalize_plate(value: &str, state: Option<&str>) -> Result<String, String> {
+  let plate: String = value
+    .chars()
+    .filter(|c| !c.is_whitespace() && *c != '-')
+    .map(|c| c.to_ascii_uppercase())
+    .collect();
+  if plate.is_empty() {
+    return Err("License plate is required.".to_string());
+  }
+  if !plate.chars().all(|c| c.is_ascii_alphanumeric()) {
+    return Err("License plate may only contain letters and numbers.".to_string());
+  }
+
+  let (format, pattern) = plate_format(state);
+  if !pattern.is_match(&plate) {
+    return Err(format!("{} plates {}.", format.state, format.rule));
+  }
+  Ok(plate)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn luhn_checksum() {
+    for (digits, valid) in [
+      ("4111111111111111", true),
+      ("4111111111111112", false),
+      ("79927398713", true),
+      ("79927398710", false),
+      ("378282246310005", true),
+    ] {
+      assert_eq!(passes_luhn(digits), valid, "{digits}");
+    }
+  }
+
+  #[test]
+  fn card_brands() {
+    for (digits, brand) in [
+      ("4111111111111111", CardBrand::Visa),
+      ("4222222222222", CardBrand::Visa),
+      ("5555555555554444", CardBrand::Mastercard),
+      ("2223003122003222", CardBrand::Mastercard),
+      ("378282246310005", CardBrand::Amex),
+      ("6011111111111117", CardBrand::Discover),
+      ("30569309025904", CardBrand::DinersClub),
+      ("3530111333300000", CardBrand::Jcb),
+      ("1234567812345670", CardBrand::Unknown),
+      ("37828224631000", CardBrand::Unknown),
+    ] {
+      assert_eq!(card_brand(digits), brand, "{digits}");
+    }
+  }
+
+  #[test]
+  fn card_numbers() {
+    for (input, expected) in [
+      ("4111 1111-1111 1111", Ok("4111111111111111")),
+      ("", Err("Card number is required.")),
+      ("4111a111", Err("Card number may only contain digits.")),
+      ("4111", Err("Card number must be between 12 and 19 digits.")),
+      ("4111111111111112", Err("Card number is not valid; check for a typo.")),
+      ("1234567812345670", Err("Card brand is not supported.")),
+    ] {
+      assert_eq!(normalize_card_number(input), expected.map(String::from).map_err(String::from), "{input:?}");
+    }
+  }
+
+  #[test]
+  fn expiration_format() {
+    for (input, expected) in [
+      ("1/30", Ok((1, 2030))),
+      (" 12 / 2031 ", Ok((12, 2031))),
+      ("13/30", Err("Expiration month must be between 01 and 12.")),
+      ("00/30", Err("Expiration month must be between 01 and 12.")),
+      ("1230", Err("Expiration must be in MM/YY format.")),
+      ("01/3", Err("Expiration must be in MM/YY format.")),
+      ("ab/30", Err("Expiration must be in MM/YY format.")),
+    ] {
+      assert_eq!(parse_expiration(input), expected.map_err(String::from), "{input:?}");
+    }
+  }
+
+  #[test]
+  fn expired_cards() {
+    assert_eq!(normalize_expiration("1/99"), Ok("01/99".to_string()));
+    assert_eq!(normalize_expiration("01/2020"), Err("Card has expired.".to_string()));
+  }
+
+  #[test]
+  fn zip_codes() {
+    for (input, valid) in [
+      ("93401", true),
+      (" 93401-1234 ", true),
+      ("9340", false),
+      ("934011", false),
+      ("93401-12", false),
+      ("9340a", false),
+      ("93401 1234", false),
+    ] {
+      assert_eq!(normalize_zip_code(input).is_ok(), valid, "{input:?}");
+    }
+    assert_eq!(normalize_zip_code(" 93401-1234 "), Ok("93401-1234".to_string()));
+  }
+
+  #[test]
+  fn plate_states() {
+    assert_eq!(normalize_plate_state(" ca "), Ok("CA".to_string()));
+    assert_eq!(normalize_plate_state("pr"), Ok("PR".to_string()));
+    assert!(normalize_plate_state("XX").is_err());
+    assert!(normalize_plate_state("California").is_err());
+  }
+
+  #[test]
+  fn plates() {
+    for (input, state, expected) in [
+      ("7abc-123", Some("CA"), Ok("7ABC123")),
+      ("LUV MY CA", Some("CA"), Ok("LUVMYCA")),
+      ("X", Some("CA"), Err("CA plates have 2 to 7 letters and numbers, like 8ABC123.")),
+      ("ABCDEFGH", Some("CA"), Err("CA plates have 2 to 7 letters and numbers, like 8ABC123.")),
+      ("123A456", Some("NV"), Ok("123A456")),
+      ("X", Some("TX"), Ok("X")),
+      ("ABCD12", Some("FL"), Ok("ABCD12")),
+      ("ABCD1234", Some("FL"), Err("FL plates have up to 7 letters and numbers, like ABCD12.")),
+      ("ABCDEFGH", Some("NY"), Ok("ABCDEFGH")),
+      ("ABCDEFGH", Some("WA"), Err("WA plates have up to 7 letters and numbers, like ABC1234.")),
+      ("ABCDEFGH", Some("MT"), Ok("ABCDEFGH")),
+      ("ABCDEFGH", None, Ok("ABCDEFGH")),
+      ("ABCDEFGHI", None, Err("US plates have at most 8 letters and numbers.")),
+      ("AB#1", None, Err("License plate may only contain letters and numbers.")),
+      (" - ", None, Err("License plate is required.")),
+    ] {
+      assert_eq!(
+        normalize_plate(input, state),
+        expected.map(String::from).map_err(String::from),
+        "{input:?} {state:?}"
+      );
+    }
+  }
+}
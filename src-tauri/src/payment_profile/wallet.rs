@@ -0,0 +1,486 @@
+use super::validation::{card_brand, CardBrand};
+use crate::error::{CommandError, CommandResult};
+use serde::{Deserialize, Serialize};
+
+/// Everything stored in the payment profile file: any number of cards and vehicles plus the card
+/// used when the frontend does not name one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Wallet {
+  pub default_profile_id: Option<String>,
+  pub profiles: Vec<StoredProfile>,
+  pub vehicles: Vec<Vehicle>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredProfile {
+  pub id: String,
+  pub label: String,
+  pub card_number: String,
+  pub card_expiration: String,
+  pub zip_code: String,
+  pub vehicle_id: Option<String>,
+}
+
+/// Mirrors the vehicle columns of `user_payment_profiles` in Supabase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vehicle {
+  pub id: String,
+  pub plate: String,
+  pub plate_state: Option<String>,
+  pub make: String,
+  pub model: String,
+  pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInput {
+  pub label: String,
+  pub card_number: String,
+  pub card_expiration: String,
+  pub zip_code: String,
+  pub vehicle_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VehicleInput {
+  pub plate: String,
+  pub plate_state: Option<String>,
+  pub make: String,
+  pub model: String,
+  pub color: Option<String>,
+}
+
+/// Single-card shape used by `load_payment_profile`/`save_payment_profile`, where `license` is the
+/// plate of the default profile's vehicle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentProfile {
+  pub card_number: String,
+  pub card_expiration: String,
+  pub zip_code: String,
+  pub license: String,
+}
+
+/// What the webview may see of a stored card: never more than the last four digits.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSummary {
+  pub id: String,
+  pub label: String,
+  pub brand: CardBrand,
+  pub last_four: String,
+  pub card_expiration: String,
+  pub vehicle_id: Option<String>,
+  pub plate: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletSummary {
+  pub default_profile_id: Option<String>,
+  pub profiles: Vec<ProfileSummary>,
+  pub vehicles: Vec<Vehicle>,
+}
+
+pub fn new_id() -> String {
+  uuid::Uuid::new_v4().to_string()
+}
+
+impl StoredProfile {
+  fn empty() -> Self {
+    StoredProfile {
+      id: new_id(),
+      label: String::new(),
+      card_number: String::new(),
+      card_expiration: String::new(),
+      zip_code: String::new(),
+      vehicle_id: None,
+    }
+  }
+
+  fn apply(&mut self, input: ProfileInput) {
+    self.label = input.label;
+    self.card_number = input.card_number;
+    self.card_expiration = input.card_expiration;
+    self.zip_code = input.zip_code;
+    self.vehicle_id = input.vehicle_id;
+  }
+}
+
+impl Vehicle {
+  fn empty() -> Self {
+    Vehicle {
+      id: new_id(),
+      plate: String::new(),
+      plate_state: None,
+      make: String::new(),
+      model: String::new(),
+      color: None,
+    }
+  }
+
+  fn apply(&mut self, input: VehicleInput) {
+    self.plate = input.plate;
+    self.plate_state = input.plate_state;
+    self.make = input.make;
+    self.model = input.model;
+    self.color = input.color;
+  }
+}
+
+impl Wallet {
+  pub fn summarize_profile(&self, profile: &StoredProfile) -> ProfileSummary {
+    let digits: String = profile.card_number.chars().filter(char::is_ascii_digit).collect();
+    let last_four = digits[digits.len().saturating_sub(4)..].to_string();
+    let plate = profile
+      .vehicle_id
+      .as_deref()
+      .and_then(|id| self.vehicle(id))
+      .map(|vehicle| vehicle.plate.clone());
+
+    ProfileSummary {
+      id: profile.id.clone(),
+      label: profile.label.clone(),
+      brand: card_brand(&digits),
+      last_four,
+      card_expiration: profile.card_expiration.clone(),
+      vehicle_id: profile.vehicle_id.clone(),
+      plate,
+    }
+  }
+
+  pub fn summary(&self) -> WalletSummary {
+    WalletSummary {
+      default_profile_id: self.default_profile_id.clone(),
+      profiles: self.profiles.iter().map(|profile| self.summarize_profile(profile)).collect(),
+      vehicles: self.vehicles.clone(),
+    }
+  }
+
+  pub fn default_profile(&self) -> Option<&StoredProfile> {
+    let id = self.default_profile_id.as_deref()?;
+    self.profiles.iter().find(|profile| profile.id == id)
+  }
+
+  pub fn vehicle(&self, id: &str) -> Option<&Vehicle> {
+    self.vehicles.iter().find(|vehicle| vehicle.id == id)
+  }
+
+  pub fn create_profile(&mut self, input: ProfileInput) -> CommandResult<StoredProfile> {
+    self.ensure_vehicle_exists(input.vehicle_id.as_deref())?;
+
+    let mut profile = StoredProfile::empty();
+    profile.apply(input);
+
+    if self.default_profile_id.is_none() {
+      self.default_profile_id = Some(profile.id.clone());
+    }
+    self.profiles.push(profile.clone());
+    Ok(profile)
+  }
+
+  pub fn update_profile(&mut self, id: &str, input: ProfileInput) -> CommandResult<StoredProfile> {
+    self.ensure_vehicle_exists(input.vehicle_id.as_deref())?;
+
+    let profile = self
+      .profiles
+      .iter_mut()
+      .find(|profile| profile.id == id)
+      .ok_or_else(|| CommandError::NotFound(format!("Payment profile {id} does not exist")))?;
+    profile.apply(input);
+    Ok(profile.clone())
+  }
+
+  pub fn remove_profile(&mut self, id: &str) -> CommandResult<()> {
+    let before = self.profiles.len();
+    self.profiles.retain(|profile| profile.id != id);
+    if self.profiles.len() == before {
+      return Err(CommandError::NotFound(format!("Payment profile {id} does not exist")));
+    }
+
+    if self.default_profile_id.as_deref() == Some(id) {
+      self.default_profile_id = self.profiles.first().map(|profile| profile.id.clone());
+    }
+    Ok(())
+  }
+
+  pub fn set_default_profile(&mut self, id: &str) -> CommandResult<()> {
+    if !self.profiles.iter().any(|profile| profile.id == id) {
+      return Err(CommandError::NotFound(format!("Payment profile {id} does not exist")));
+    }
+
+    self.default_profile_id = Some(id.to_string());
+    Ok(())
+  }
+
+  pub fn create_vehicle(&mut self, input: VehicleInput) -> Vehicle {
+    let mut vehicle = Vehicle::empty();
+    vehicle.apply(input);
+
+    self.vehicles.push(vehicle.clone());
+    vehicle
+  }
+
+  pub fn update_vehicle(&mut self, id: &str, input: VehicleInput) -> CommandResult<Vehicle> {
+    let vehicle = self
+      .vehicles
+      .iter_mut()
+      .find(|vehicle| vehicle.id == id)
+      .ok_or_else(|| CommandError::NotFound(format!("Vehicle {id} does not exist")))?;
+    vehicle.apply(input);
+    Ok(vehicle.clone())
+  }
+
+  pub fn remove_vehicle(&mut self, id: &str) -> CommandResult<()> {
+    if let Some(profile) = self.profiles.iter().find(|profile| profile.vehicle_id.as_deref() == Some(id)) {
+      return Err(CommandError::Conflict(format!(
+        "Vehicle {id} is still used by payment profile \"{}\"",
+        profile.label
+      )));
+    }
+
+    let before = self.vehicles.len();
+    self.vehicles.retain(|vehicle| vehicle.id != id);
+    if self.vehicles.len() == before {
+      return Err(CommandError::NotFound(format!("Vehicle {id} does not exist")));
+    }
+    Ok(())
+  }
+
+  /// Flattens the default profile and its vehicle into the single-card shape.
+  pub fn default_payment_profile(&self) -> Option<PaymentProfile> {
+    let profile = self.default_profile()?;
+    let license = profile
+      .vehicle_id
+      .as_deref()
+      .and_then(|id| self.vehicle(id))
+      .map(|vehicle| vehicle.plate.clone())
+      .unwrap_or_default();
+
+    Some(PaymentProfile {
+      card_number: profile.card_number.clone(),
+      card_expiration: profile.card_expiration.clone(),
+      zip_code: profile.zip_code.clone(),
+      license,
+    })
+  }
+
+  /// Writes the single-card shape onto the default profile, creating the profile and its vehicle
+  /// when they do not exist yet. A new plate is written onto the default profile's vehicle unless
+  /// another profile uses that vehicle too, in which case the default profile gets its own.
+  pub fn save_default_payment_profile(&mut self, payment_profile: PaymentProfile) {
+    let default_id = self.default_profile().map(|profile| profile.id.clone());
+    let existing_vehicle = self
+      .default_profile()
+      .and_then(|profile| profile.vehicle_id.clone())
+      .filter(|id| {
+        let same_plate = self.vehicle(id).is_some_and(|vehicle| vehicle.plate == payment_profile.license);
+        same_plate || !self.vehicle_used_by_others(id, default_id.as_deref())
+      })
+      .and_then(|id| self.vehicles.iter_mut().find(|vehicle| vehicle.id == id));
+
+    let vehicle_id = match existing_vehicle {
+      Some(vehicle) => {
+        vehicle.plate = payment_profile.license;
+        vehicle.id.clone()
+      }
+      None => {
+        self
+          .create_vehicle(VehicleInput {
+            plate: payment_profile.license,
+            plate_state: None,
+            make: String::new(),
+            model: String::new(),
+            color: None,
+          })
+          .id
+      }
+    };
+
+    let input = ProfileInput {
+      label: "Default".to_string(),
+      card_number: payment_profile.card_number,
+      card_expiration: payment_profile.card_expiration,
+      zip_code: payment_profile.zip_code,
+      vehicle_id: Some(vehicle_id),
+    };
+
+    match default_id.and_then(|id| self.profiles.iter_mut().find(|profile| profile.id == id)) {
+      Some(profile) => profile.apply(ProfileInput {
+        label: profile.label.clone(),
+        ..input
+      }),
+      None => {
+        let mut profile = StoredProfile::empty();
+        profile.apply(input);
+        self.default_profile_id = Some(profile.id.clone());
+        self.profiles.push(profile);
+      }
+    }
+  }
+
+  fn vehicle_used_by_others(&self, vehicle_id: &str, profile_id: Option<&str>) -> bool {
+    self
+      .profiles
+      .iter()
+      .any(|profile| Some(profile.id.as_str()) != profile_id && profile.vehicle_id.as_deref() == Some(vehicle_id))
+  }
+
+  fn ensure_vehicle_exists(&self, vehicle_id: Option<&str>) -> CommandResult<()> {
+    match vehicle_id {
+      Some(id) if self.vehicle(id).is_none() => Err(CommandError::NotFound(format!("Vehicle {id} does not exist"))),
+      _ => Ok(()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn profile_input(label: &str, vehicle_id: Option<&str>) -> ProfileInput {
+    ProfileInput {
+      label: label.to_string(),
+      card_number: "4111111111111111".to_string(),
+      card_expiration: "12/30".to_string(),
+      zip_code: "93401".to_string(),
+      vehicle_id: vehicle_id.map(String::from),
+    }
+  }
+
+  fn vehicle_input(plate: &str) -> VehicleInput {
+    VehicleInput {
+      plate: plate.to_string(),
+      plate_state: Some("CA".to_string()),
+      make: "Honda".to_string(),
+      model: "Civic".to_string(),
+      color: None,
+    }
+  }
+
+  fn payment_profile(license: &str) -> PaymentProfile {
+    PaymentProfile {
+      card_number: "5555555555554444".to_string(),
+      card_expiration: "01/31".to_string(),
+      zip_code: "93405".to_string(),
+      license: license.to_string(),
+    }
+  }
+
+  #[test]
+  fn create_update_and_remove_profiles() {
+    let mut wallet = Wallet::default();
+    let car = wallet.create_vehicle(vehicle_input("7ABC123"));
+
+    let first = wallet.create_profile(profile_input("Personal", Some(&car.id))).unwrap();
+    let second = wallet.create_profile(profile_input("Work", None)).unwrap();
+    assert_eq!(wallet.default_profile_id.as_deref(), Some(first.id.as_str()));
+
+    let updated = wallet.update_profile(&second.id, profile_input("Office", Some(&car.id))).unwrap();
+    assert_eq!((updated.id.as_str(), updated.label.as_str()), (second.id.as_str(), "Office"));
+    assert_eq!(wallet.summary().profiles[1].plate.as_deref(), Some("7ABC123"));
+    assert_eq!(wallet.summary().profiles[1].last_four, "1111");
+
+    wallet.remove_profile(&second.id).unwrap();
+    assert_eq!(wallet.profiles.len(), 1);
+    assert!(matches!(wallet.remove_profile(&second.id), Err(CommandError::NotFound(_))));
+    assert!(matches!(
+      wallet.update_profile(&second.id, profile_input("Office", None)),
+      Err(CommandError::NotFound(_))
+    ));
+  }
+
+  #[test]
+  fn profiles_must_name_an_existing_vehicle() {
+    let mut wallet = Wallet::default();
+    assert!(matches!(
+      wallet.create_profile(profile_input("Personal", Some("missing"))),
+      Err(CommandError::NotFound(_))
+    ));
+    assert!(wallet.profiles.is_empty());
+  }
+
+  #[test]
+  fn removing_the_default_promotes_the_next_profile() {
+    let mut wallet = Wallet::default();
+    let first = wallet.create_profile(profile_input("Personal", None)).unwrap();
+    let second = wallet.create_profile(profile_input("Work", None)).unwrap();
+    let third = wallet.create_profile(profile_input("Spare", None)).unwrap();
+    wallet.set_default_profile(&second.id).unwrap();
+
+    wallet.remove_profile(&first.id).unwrap();
+    assert_eq!(wallet.default_profile_id.as_deref(), Some(second.id.as_str()));
+
+    wallet.remove_profile(&second.id).unwrap();
+    assert_eq!(wallet.default_profile_id.as_deref(), Some(third.id.as_str()));
+
+    wallet.remove_profile(&third.id).unwrap();
+    assert_eq!(wallet.default_profile_id, None);
+    assert!(wallet.default_payment_profile().is_none());
+  }
+
+  #[test]
+  fn a_vehicle_in_use_cannot_be_removed() {
+    let mut wallet = Wallet::default();
+    let car = wallet.create_vehicle(vehicle_input("7ABC123"));
+    let profile = wallet.create_profile(profile_input("Personal", Some(&car.id))).unwrap();
+
+    assert!(matches!(wallet.remove_vehicle(&car.id), Err(CommandError::Conflict(_))));
+    assert_eq!(wallet.vehicles.len(), 1);
+
+    wallet.update_profile(&profile.id, profile_input("Personal", None)).unwrap();
+    wallet.remove_vehicle(&car.id).unwrap();
+    assert!(wallet.vehicles.is_empty());
+    assert!(matches!(wallet.remove_vehicle(&car.id), Err(CommandError::NotFound(_))));
+  }
+
+  #[test]
+  fn save_default_payment_profile_creates_the_profile_and_vehicle() {
+    let mut wallet = Wallet::default();
+
+    wallet.save_default_payment_profile(payment_profile("7ABC123"));
+
+    assert_eq!(wallet.profiles.len(), 1);
+    assert_eq!(wallet.vehicles.len(), 1);
+    let saved = wallet.default_payment_profile().unwrap();
+    assert_eq!((saved.card_number.as_str(), saved.license.as_str()), ("5555555555554444", "7ABC123"));
+  }
+
+  #[test]
+  fn save_default_payment_profile_rewrites_an_unshared_plate_in_place() {
+    let mut wallet = Wallet::default();
+    let car = wallet.create_vehicle(vehicle_input("7ABC123"));
+    let profile = wallet.create_profile(profile_input("Personal", Some(&car.id))).unwrap();
+
+    wallet.save_default_payment_profile(payment_profile("8XYZ987"));
+
+    assert_eq!(wallet.vehicles.len(), 1);
+    assert_eq!(wallet.vehicle(&car.id).unwrap().plate, "8XYZ987");
+    let saved = wallet.default_profile().unwrap();
+    assert_eq!((saved.id.as_str(), saved.label.as_str()), (profile.id.as_str(), "Personal"));
+    assert_eq!(saved.card_number, "5555555555554444");
+  }
+
+  #[test]
+  fn save_default_payment_profile_leaves_a_shared_vehicle_alone() {
+    let mut wallet = Wallet::default();
+    let car = wallet.create_vehicle(vehicle_input("7ABC123"));
+    wallet.create_profile(profile_input("Personal", Some(&car.id))).unwrap();
+    let work = wallet.create_profile(profile_input("Work", Some(&car.id))).unwrap();
+
+    wallet.save_default_payment_profile(payment_profile("7ABC123"));
+    assert_eq!(wallet.vehicles.len(), 1);
+
+    wallet.save_default_payment_profile(payment_profile("8XYZ987"));
+    assert_eq!(wallet.vehicles.len(), 2);
+    assert_eq!(wallet.vehicle(&car.id).unwrap().plate, "7ABC123");
+    assert_eq!(wallet.default_payment_profile().unwrap().license, "8XYZ987");
+    let work = wallet.profiles.iter().find(|profile| profile.id == work.id).unwrap();
+    assert_eq!(work.vehicle_id.as_deref(), Some(car.id.as_str()));
+  }
+}
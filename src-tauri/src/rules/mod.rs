@@ -0,0 +1,313 @@
+//! Parking rules: structured tariffs for every bundled zone, and what a stay costs under them given
+//! the enforcement calendar. Schedules are in San Luis Obispo local time.
+
+mod tariff;
+
+use crate::calendar::{EnforcementCalendar, EnforcementStatus, TIME_ZONE};
+use crate::error::{CommandError, CommandResult};
+use crate::payment_profile::FieldError;
+use crate::zones::{ZoneCategory, ZoneIndex};
+use chrono::DateTime;
+use chrono_tz::Tz;
+use serde::Serialize;
+
+pub use tariff::{format_cents, format_local, format_minutes, Estimate, MeterSchedule, Tariff, Violation, ViolationKind};
+
+pub const MAX_ESTIMATE_MINUTES: u32 = 7 * 24 * 60;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnforcedPeriod {
+  pub starts_at: String,
+  pub ends_at: String,
+  pub minutes: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParkingCostEstimate {
+  pub zone_number: String,
+  pub category: ZoneCategory,
+  pub rate: String,
+  pub starts_at: String,
+  pub ends_at: String,
+  pub duration_minutes: u32,
+  /// Minutes of the stay that are paid (or need a permit); the rest is free.
+  pub enforced_minutes: u32,
+  pub enforced_periods: Vec<EnforcedPeriod>,
+  pub total_cents: u32,
+  pub total: String,
+  pub daily_max_applied: bool,
+  pub max_stay_minutes: Option<u32>,
+  pub violations: Vec<Violation>,
+  pub schedule: String,
+  /// The schedule behind this price is unconfirmed, so the total is a guess; see `warnings`.
+  pub provisional: bool,
+  pub warnings: Vec<String>,
+}
+
+/// A zone's tariff and how to name it when a zone number covers several.
+struct Candidate<'a> {
+  label: String,
+  rate: &'a str,
+  tariff: &'a Tariff,
+}
+
+/// Estimates a stay in PayByPhone zone `zone` (or residential district id) from `start`, an RFC 3339
+/// timestamp, for `duration_minutes`.
+#[tauri::command]
+pub fn estimate_parking_cost(
+  zones: tauri::State<'_, ZoneIndex>,
+  calendar: tauri::State<'_, EnforcementCalendar>,
+  zone: String,
+  start: String,
+  duration_minutes: u32,
+) -> CommandResult<ParkingCostEstimate> {
+  let (zone, start) = validate_estimate(&zone, &start, duration_minutes)?;
+  estimate(&zones, &calendar, &zone, start, duration_minutes)
+}
+
+fn validate_estimate(zone: &str, start: &str, duration_minutes: u32) -> CommandResult<(String, DateTime<Tz>)> {
+  let zone = zone.trim();
+  let mut fields = Vec::new();
+  if zone.is_empty() {
+    fields.push(FieldError {
+      field: "zone".to_string(),
+      message: "Zone number is required.".to_string(),
+    });
+  }
+  let start = DateTime::parse_from_rfc3339(start.trim()).ok();
+  if start.is_none() {
+    fields.push(FieldError {
+      field: "start".to_string(),
+      message: "Start must be a timestamp like 2026-02-22T20:00:00-08:00.".to_string(),
+    });
+  }
+  if !(1..=MAX_ESTIMATE_MINUTES).contains(&duration_minutes) {
+    fields.push(FieldError {
+      field: "durationMinutes".to_string(),
+      message: format!("Duration must be between 1 and {MAX_ESTIMATE_MINUTES} minutes."),
+    });
+  }
+
+  match start {
+    Some(start) if fields.is_empty() => Ok((zone.to_string(), start.with_timezone(&TIME_ZONE))),
+    _ => Err(CommandError::Validation { fields }),
+  }
+}
+
+pub fn estimate(
+  index: &ZoneIndex,
+  calendar: &EnforcementCalendar,
+  zone: &str,
+  start: DateTime<Tz>,
+  duration_minutes: u32,
+) -> CommandResult<ParkingCostEstimate> {
+  let (category, candidates) = candidates(index, zone)?;
+  let zone_calendar = calendar.for_zone(zone, category);
+  let end = start + chrono::Duration::minutes(i64::from(duration_minutes));
+
+  let mut estimates: Vec<(Candidate<'_>, Estimate)> = candidates
+    .into_iter()
+    .map(|candidate| {
+      let estimate = candidate.tariff.estimate(start, end, &zone_calendar);
+      (candidate, estimate)
+    })
+    .collect();
+  // Polygons sharing a zone number can differ (e.g. a lot with a time limit next to plain meters);
+  // quote the strictest so the user is never told a stay is fine when part of the zone disagrees.
+  let key = |estimate: &Estimate| (estimate.violations.len(), estimate.total_cents);
+  let labels = disagreeing_labels(&estimates, |(_, estimate)| key(estimate));
+  estimates.sort_by_key(|(_, estimate)| std::cmp::Reverse(key(estimate)));
+  let (candidate, estimate) = estimates.swap_remove(0);
+  let warnings = strictest_warning(zone, &labels, &candidate)
+    .into_iter()
+    .chain(provisional_warning(zone, &candidate))
+    .collect();
+
+  Ok(ParkingCostEstimate {
+    zone_number: zone.to_string(),
+    category,
+    rate: candidate.rate.to_string(),
+    starts_at: timestamp(start),
+    ends_at: timestamp(end),
+    duration_minutes,
+    enforced_minutes: estimate.enforced_minutes,
+    enforced_periods: estimate
+      .periods
+      .iter()
+      .map(|period| EnforcedPeriod {
+        starts_at: timestamp(period.starts_at),
+        ends_at: timestamp(period.ends_at),
+        minutes: period.minutes,
+      })
+      .collect(),
+    total_cents: estimate.total_cents,
+    total: format_cents(estimate.total_cents),
+    daily_max_applied: estimate.daily_max_applied,
+    max_stay_minutes: candidate.tariff.max_stay_minutes,
+    violations: estimate.violations,
+    schedule: candidate.tariff.description.clone(),
+    provisional: candidate.tariff.provisional,
+    warnings,
+  })
+}
+
+/// Whether `zone` is enforced at `at`, for the calendar's `is_enforced` command. A zone number whose
+/// polygons disagree counts as enforced if any of them is.
+pub fn enforcement_status(
+  index: &ZoneIndex,
+  calendar: &EnforcementCalendar,
+  zone: &str,
+  at: DateTime<Tz>,
+) -> CommandResult<EnforcementStatus> {
+  let (category, candidates) = candidates(index, zone)?;
+  let zone_calendar = calendar.for_zone(zone, category);
+
+  let mut statuses: Vec<_> = candidates
+    .into_iter()
+    .map(|candidate| {
+      let status = candidate.tariff.status_at(at, &zone_calendar);
+      (candidate, status)
+    })
+    .collect();
+  let labels = disagreeing_labels(&statuses, |(_, status)| (status.enforced, status.changes_at));
+  statuses.sort_by_key(|(_, status)| !status.enforced);
+  let (candidate, status) = statuses.swap_remove(0);
+
+  Ok(EnforcementStatus {
+    zone_number: zone.to_string(),
+    category,
+    at: timestamp(at),
+    enforced: status.enforced,
+    parking_allowed: status.closure.is_none(),
+    reason: match &status.closure {
+      Some(closure) => format!("No parking during {closure}. {}", status.reason),
+      None => status.reason,
+    },
+    changes_at: status.changes_at.map(timestamp),
+    warnings: strictest_warning(zone, &labels, &candidate)
+      .into_iter()
+      .chain(provisional_warning(zone, &candidate))
+      .collect(),
+  })
+}
+
+/// Labels of all candidates if `key` tells any two apart, else none: a zone whose polygons agree
+/// needs no warning.
+fn disagreeing_labels<T, K: PartialEq>(
+  results: &[(Candidate<'_>, T)],
+  key: impl Fn(&(Candidate<'_>, T)) -> K,
+) -> Vec<String> {
+  let differ = results.windows(2).any(|pair| key(&pair[0]) != key(&pair[1]));
+  if differ {
+    results.iter().map(|(candidate, _)| candidate.label.clone()).collect()
+  } else {
+    Vec::new()
+  }
+}
+
+fn strictest_warning(zone: &str, labels: &[String], chosen: &Candidate<'_>) -> Option<String> {
+  (labels.len() > 1).then(|| {
+    format!(
+      "Zone {zone} covers {} with different rules; this assumes {}, the strictest.",
+      labels.join(", "),
+      chosen.label
+    )
+  })
+}
+
+fn provisional_warning(zone: &str, chosen: &Candidate<'_>) -> Option<String> {
+  chosen.tariff.provisional.then(|| {
+    format!("Hours for zone {zone} are provisional, not confirmed by the city; check the posted signs.")
+  })
+}
+
+/// Distinct tariffs of the meter polygons numbered `zone`, else of the residential district `zone`.
+fn candidates<'a>(index: &'a ZoneIndex, zone: &'a str) -> CommandResult<(ZoneCategory, Vec<Candidate<'a>>)> {
+  let mut found = false;
+  let mut candidates: Vec<Candidate<'a>> = Vec::new();
+  for paid in index.paid_zones_numbered(zone) {
+    found = true;
+    let Some(tariff) = paid.tariff.as_ref() else {
+      continue;
+    };
+    if candidates.iter().any(|candidate| candidate.tariff == tariff) {
+      continue;
+    }
+    let label = match &paid.name {
+      Some(name) if paid.zone_type == "Lot" => format!("Lot {name}"),
+      Some(name) => name.clone(),
+      None => format!("{} meters", paid.zone_type),
+    };
+    candidates.push(Candidate {
+      label,
+      rate: &paid.meter_zone,
+      tariff,
+    });
+  }
+  if found {
+    if candidates.is_empty() {
+      return Err(CommandError::Unsupported(format!("No rate schedule is known for zone {zone}.")));
+    }
+    return Ok((ZoneCategory::Paid, candidates));
+  }
+
+  let residential = index
+    .residential_zone(zone)
+    .ok_or_else(|| CommandError::NotFound(format!("Zone {zone} is not in the bundled zone data.")))?;
+  let tariff = residential
+    .tariff
+    .as_ref()
+    .ok_or_else(|| CommandError::Unsupported(format!("No permit hours are known for zone {zone}.")))?;
+  Ok((
+    ZoneCategory::Residential,
+    vec![Candidate {
+      label: residential.description.clone(),
+      rate: "Permit required",
+      tariff,
+    }],
+  ))
+}
+
+pub fn timestamp(value: DateTime<Tz>) -> String {
+  value.to_rfc3339_opts(chrono::SecondsFormat::Secs, false)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn estimates_on_provisional_schedules_say_so() {
+    let index = ZoneIndex::load_bundled().unwrap();
+    let calendar = EnforcementCalendar::load_bundled().unwrap();
+    let (zone, start) = validate_estimate("80511", "2026-02-23T12:00:00-08:00", 60).unwrap();
+
+    let estimate = estimate(&index, &calendar, &zone, start, 60).unwrap();
+    assert!(estimate.provisional);
+    assert!(estimate.warnings.iter().any(|warning| warning.contains("provisional")));
+
+    let status = enforcement_status(&index, &calendar, &zone, start).unwrap();
+    assert!(status.warnings.iter().any(|warning| warning.contains("provisional")));
+  }
+
+  #[test]
+  fn estimate_validation() {
+    let cases = [
+      ("80511", "2026-02-23T12:00:00-08:00", 60, None),
+      ("", "2026-02-23T12:00:00-08:00", 60, Some(vec!["zone"])),
+      ("80511", "next tuesday", 60, Some(vec!["start"])),
+      ("80511", "2026-02-23T12:00:00-08:00", 0, Some(vec!["durationMinutes"])),
+      (" ", "", MAX_ESTIMATE_MINUTES + 1, Some(vec!["zone", "start", "durationMinutes"])),
+    ];
+    for (zone, start, minutes, expected) in cases {
+      let fields = match validate_estimate(zone, start, minutes) {
+        Ok(_) => None,
+        Err(CommandError::Validation { fields }) => Some(fields.into_iter().map(|field| field.field).collect()),
+        Err(error) => panic!("unexpected error {error:?}"),
+      };
+      assert_eq!(fields, expected.map(|fields| fields.iter().map(|field| field.to_string()).collect::<Vec<_>>()));
+    }
+  }
+}
@@ -0,0 +1,703 @@
+//! Structured tariffs parsed from the strings in the bundled data (`"$2.75/hr"`, `"3 hour limit"`,
+//! `" $8 daily max"`, `"10 am - 6 pm Mon-Wed"`), and what a stay costs under one.
+
+use crate::calendar::{self, OverrideEffect, ZoneCalendar};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+const SECONDS_PER_HOUR: u64 = 60 * 60;
+/// How far `Tariff::status_at` looks for the next change.
+const STATUS_HORIZON_DAYS: i64 = 7;
+const WEEKDAYS: [Weekday; 7] = [
+  Weekday::Mon,
+  Weekday::Tue,
+  Weekday::Wed,
+  Weekday::Thu,
+  Weekday::Fri,
+  Weekday::Sat,
+  Weekday::Sun,
+];
+
+/// Enforcement hours and exemptions shared by every meter of one zone type, from
+/// `data/slo-meter-schedules.json`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeterSchedule {
+  #[serde(rename = "type")]
+  pub zone_type: String,
+  pub hours: Vec<String>,
+  pub free_on_sundays: bool,
+  pub free_on_holidays: bool,
+  /// The hours were not taken from posted signs or a city source, so prices built on them are guesses.
+  #[serde(default)]
+  pub provisional: bool,
+  pub description: String,
+}
+
+/// Enforcement on one weekday, in minutes after local midnight. `end_minute` passes
+/// `MINUTES_PER_DAY` when enforcement runs past midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnforcementWindow {
+  pub weekday: Weekday,
+  pub start_minute: u32,
+  pub end_minute: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tariff {
+  pub hourly_rate_cents: u32,
+  pub daily_max_cents: Option<u32>,
+  pub max_stay_minutes: Option<u32>,
+  pub enforcement: Vec<EnforcementWindow>,
+  pub free_on_sundays: bool,
+  pub free_on_holidays: bool,
+  /// Residential districts: nothing to pay, but parking while enforced needs a permit.
+  pub permit_required: bool,
+  /// Enforcement hours are unconfirmed; see `MeterSchedule::provisional`.
+  pub provisional: bool,
+  pub description: String,
+}
+
+type Span = (DateTime<Tz>, DateTime<Tz>);
+
+/// Enforced time inside a stay. Back-to-back windows are merged, so each period is one stretch the
+/// time limit applies to.
+#[derive(Debug, Clone)]
+pub struct EnforcedPeriod {
+  pub starts_at: DateTime<Tz>,
+  pub ends_at: DateTime<Tz>,
+  pub minutes: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+  MaxStay,
+  PermitRequired,
+  Closed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Violation {
+  pub kind: ViolationKind,
+  pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Estimate {
+  pub periods: Vec<EnforcedPeriod>,
+  pub enforced_minutes: u32,
+  pub total_cents: u32,
+  pub daily_max_applied: bool,
+  pub violations: Vec<Violation>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TariffStatus {
+  pub enforced: bool,
+  /// Name of the closure in effect, if parking is not allowed at all.
+  pub closure: Option<String>,
+  pub reason: String,
+  pub changes_at: Option<DateTime<Tz>>,
+}
+
+struct Patterns {
+  rate: Regex,
+  time_limit: Regex,
+  daily_max: Regex,
+  hours: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+  static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+  PATTERNS.get_or_init(|| {
+    let compile = |pattern: &str| Regex::new(pattern).expect("tariff pattern is valid");
+    let time = r"\d{1,2}(?::\d{2})?\s*[ap]m";
+    Patterns {
+      rate: compile(r"(?i)^\$(\d+(?:\.\d{1,2})?)\s*/\s*(?:hr|hour)$"),
+      time_limit: compile(r"(?i)\b(\d+)\s*(hour|hr|minute|min)s?\s+limit\b"),
+      daily_max: compile(r"(?i)\$(\d+(?:\.\d{1,2})?)\s+daily\s+max\b"),
+      hours: compile(&format!(r"(?i)^(?:({time})\s*-\s*({time})|24\s*hrs?)\s*,?\s*(.+)$")),
+    }
+  })
+}
+
+impl Tariff {
+  /// A meter polygon: `rate` like `"$2.75/hr"` and an optional `label` like `"3 hour limit"` or
+  /// `"$8 daily max"`, enforced as `schedule` says.
+  pub fn metered(rate: &str, label: &str, schedule: &MeterSchedule) -> Result<Self, String> {
+    let patterns = patterns();
+    let hourly_rate_cents = patterns
+      .rate
+      .captures(rate.trim())
+      .and_then(|captures| parse_cents(&captures[1]))
+      .ok_or_else(|| format!("Unrecognized meter rate {rate:?}"))?;
+    let max_stay_minutes = patterns.time_limit.captures(label).and_then(|captures| {
+      let amount: u32 = captures[1].parse().ok()?;
+      let unit = captures[2].to_ascii_lowercase();
+      Some(if unit.starts_with('h') { amount * 60 } else { amount })
+    });
+    let daily_max_cents = patterns
+      .daily_max
+      .captures(label)
+      .and_then(|captures| parse_cents(&captures[1]));
+
+    Ok(Tariff {
+      hourly_rate_cents,
+      daily_max_cents,
+      max_stay_minutes,
+      enforcement: parse_all_hours(&schedule.hours)?,
+      free_on_sundays: schedule.free_on_sundays,
+      free_on_holidays: schedule.free_on_holidays,
+      permit_required: false,
+      provisional: schedule.provisional,
+      description: schedule.description.clone(),
+    })
+  }
+
+  /// A residential permit district enforced during `hours`, like `"8 am - 5 pm Mon-Fri"`.
+  pub fn residential(hours: &str) -> Result<Self, String> {
+    Ok(Tariff {
+      hourly_rate_cents: 0,
+      daily_max_cents: None,
+      max_stay_minutes: None,
+      enforcement: parse_hours(hours)?,
+      free_on_sundays: false,
+      free_on_holidays: false,
+      permit_required: true,
+      provisional: false,
+      description: format!("Residential permit required {}", hours.trim()),
+    })
+  }
+
+  /// Why the tariff is off for all of `date` before looking at its hours (`"Sundays"` or the
+  /// holiday), if it is.
+  pub fn free_day(&self, date: NaiveDate, calendar: &ZoneCalendar<'_>) -> Option<String> {
+    if self.free_on_sundays && date.weekday() == Weekday::Sun {
+      return Some("Sundays".to_string());
+    }
+    calendar
+      .meter_holiday(date)
+      .filter(|_| self.free_on_holidays)
+      .map(str::to_string)
+  }
+
+  /// Enforced stretches between `start` and `end`, sorted and merged: the weekly hours on days that
+  /// are not free, plus `enforced` overrides, minus `free` ones.
+  fn enforced_between(&self, start: DateTime<Tz>, end: DateTime<Tz>, calendar: &ZoneCalendar<'_>) -> Vec<Span> {
+    let clip = |from: DateTime<Tz>, to: DateTime<Tz>| {
+      let (from, to) = (from.max(start), to.min(end));
+      (from < to).then_some((from, to))
+    };
+
+    let mut pieces = Vec::new();
+    // Start a day early for windows that run past midnight into the stay.
+    let mut day = start.date_naive() - Duration::days(1);
+    while day <= end.date_naive() {
+      if self.free_day(day, calendar).is_none() {
+        pieces.extend(
+          self
+            .enforcement
+            .iter()
+            .filter(|window| window.weekday == day.weekday())
+            .filter_map(|window| clip(local_time(day, window.start_minute), local_time(day, window.end_minute))),
+        );
+      }
+      day += Duration::days(1);
+    }
+    pieces.extend(
+      calendar
+        .overrides(OverrideEffect::Enforced)
+        .filter_map(|entry| clip(entry.starts_at, entry.ends_at)),
+    );
+
+    calendar
+      .overrides(OverrideEffect::Free)
+      .fold(merge(pieces), |pieces, entry| subtract(pieces, entry.starts_at, entry.ends_at))
+  }
+
+  /// Cost and rule violations of parking from `start` to `end`. Charges are prorated to the second
+  /// and rounded up to the cent per calendar day, then capped at the daily maximum.
+  pub fn estimate(&self, start: DateTime<Tz>, end: DateTime<Tz>, calendar: &ZoneCalendar<'_>) -> Estimate {
+    let enforced = self.enforced_between(start, end, calendar);
+
+    let mut daily_seconds: BTreeMap<NaiveDate, u64> = BTreeMap::new();
+    for (from, to) in &enforced {
+      let mut from = *from;
+      while from < *to {
+        let until = local_time(from.date_naive() + Duration::days(1), 0).min(*to);
+        *daily_seconds.entry(from.date_naive()).or_default() += (until - from).num_seconds().max(0) as u64;
+        from = until;
+      }
+    }
+
+    let mut total_cents = 0;
+    let mut daily_max_applied = false;
+    for seconds in daily_seconds.values() {
+      let mut cents = (seconds * u64::from(self.hourly_rate_cents)).div_ceil(SECONDS_PER_HOUR) as u32;
+      if let Some(daily_max) = self.daily_max_cents.filter(|daily_max| cents > *daily_max) {
+        cents = daily_max;
+        daily_max_applied = true;
+      }
+      total_cents += cents;
+    }
+
+    let periods: Vec<EnforcedPeriod> = enforced
+      .into_iter()
+      .map(|(starts_at, ends_at)| EnforcedPeriod {
+        starts_at,
+        ends_at,
+        minutes: minutes_between(starts_at, ends_at),
+      })
+      .collect();
+    let enforced_minutes = periods.iter().map(|period| period.minutes).sum();
+    let mut violations = self.violations(&periods);
+    violations.extend(
+      calendar
+        .overrides(OverrideEffect::Closed)
+        .filter(|entry| entry.starts_at < end && start < entry.ends_at)
+        .map(|entry| Violation {
+          kind: ViolationKind::Closed,
+          message: format!(
+            "No parking during {} from {} to {}.",
+            entry.name,
+            format_local(entry.starts_at),
+            format_local(entry.ends_at)
+          ),
+        }),
+    );
+
+    Estimate {
+      periods,
+      enforced_minutes,
+      total_cents,
+      daily_max_applied,
+      violations,
+    }
+  }
+
+  /// Whether the tariff is enforced at `at`, why, and when that next changes.
+  pub fn status_at(&self, at: DateTime<Tz>, calendar: &ZoneCalendar<'_>) -> TariffStatus {
+    let horizon = at + Duration::days(STATUS_HORIZON_DAYS);
+    let enforced = self.enforced_between(at, horizon, calendar);
+    let current = enforced.first().filter(|(from, _)| *from <= at);
+    let changes_at = match current {
+      Some((_, to)) => Some(*to).filter(|to| *to < horizon),
+      None => enforced.first().map(|(from, _)| *from),
+    };
+
+    let reason = if let Some(entry) = calendar.overrides(OverrideEffect::Free).find(|entry| entry.covers(at)) {
+      format!("Not enforced during {}.", entry.name)
+    } else if let Some(entry) = calendar.overrides(OverrideEffect::Enforced).find(|entry| entry.covers(at)) {
+      format!("Enforced during {}.", entry.name)
+    } else if current.is_some() && self.permit_required {
+      format!("{}.", self.description)
+    } else if current.is_some() {
+      "Meters are enforced.".to_string()
+    } else if let Some(free_day) = self.free_day(at.date_naive(), calendar) {
+      format!("Free on {free_day}.")
+    } else {
+      "Outside enforcement hours.".to_string()
+    };
+
+    TariffStatus {
+      enforced: current.is_some(),
+      closure: calendar
+        .overrides(OverrideEffect::Closed)
+        .find(|entry| entry.covers(at))
+        .map(|entry| entry.name.clone()),
+      reason,
+      changes_at,
+    }
+  }
+
+  fn violations(&self, periods: &[EnforcedPeriod]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    if let Some(limit) = self.max_stay_minutes {
+      for period in periods.iter().filter(|period| period.minutes > limit) {
+        let move_by = period.starts_at + Duration::minutes(i64::from(limit));
+        violations.push(Violation {
+          kind: ViolationKind::MaxStay,
+          message: format!(
+            "{} time limit: this stay is enforced for {} in a row from {}. Move the car by {}.",
+            format_minutes(limit),
+            format_minutes(period.minutes),
+            format_local(period.starts_at),
+            format_local(move_by)
+          ),
+        });
+      }
+    }
+    if self.permit_required {
+      if let Some(first) = periods.first() {
+        violations.push(Violation {
+          kind: ViolationKind::PermitRequired,
+          message: format!(
+            "A residential permit is required from {} ({}).",
+            format_local(first.starts_at),
+            self.description
+          ),
+        });
+      }
+    }
+    violations
+  }
+}
+
+/// Sorts `pieces` and joins the ones that touch or overlap.
+fn merge(mut pieces: Vec<Span>) -> Vec<Span> {
+  pieces.sort();
+  let mut merged: Vec<Span> = Vec::new();
+  for (from, to) in pieces {
+    match merged.last_mut() {
+      Some(last) if from <= last.1 => last.1 = last.1.max(to),
+      _ => merged.push((from, to)),
+    }
+  }
+  merged
+}
+
+/// `pieces` without `[from, to)`.
+fn subtract(pieces: Vec<Span>, from: DateTime<Tz>, to: DateTime<Tz>) -> Vec<Span> {
+  pieces
+    .into_iter()
+    .flat_map(|(start, end)| [(start, end.min(from)), (start.max(to), end)])
+    .filter(|(start, end)| start < end)
+    .collect()
+}
+
+fn minutes_between(from: DateTime<Tz>, to: DateTime<Tz>) -> u32 {
+  ((to - from).num_seconds().max(0) as u64).div_ceil(60) as u32
+}
+
+/// `minute` minutes after local midnight of `date`.
+fn local_time(date: NaiveDate, minute: u32) -> DateTime<Tz> {
+  calendar::local_datetime(date.and_time(NaiveTime::MIN) + Duration::minutes(i64::from(minute)))
+}
+
+pub fn format_local(time: DateTime<Tz>) -> String {
+  time.format("%a %-I:%M %p").to_string()
+}
+
+pub fn format_minutes(minutes: u32) -> String {
+  match (minutes / 60, minutes % 60) {
+    (0, minutes) => format!("{minutes} min"),
+    (hours, 0) => format!("{hours} h"),
+    (hours, minutes) => format!("{hours} h {minutes} min"),
+  }
+}
+
+pub fn format_cents(cents: u32) -> String {
+  format!("${}.{:02}", cents / 100, cents % 100)
+}
+
+fn parse_cents(amount: &str) -> Option<u32> {
+  let (dollars, cents) = amount.split_once('.').unwrap_or((amount, "0"));
+  let cents = format!("{cents:0<2}");
+  Some(dollars.parse::<u32>().ok()? * 100 + cents.parse::<u32>().ok()?)
+}
+
+fn parse_all_hours(hours: &[String]) -> Result<Vec<EnforcementWindow>, String> {
+  let mut windows = Vec::new();
+  for entry in hours {
+    windows.extend(parse_hours(entry)?);
+  }
+  Ok(windows)
+}
+
+/// `"10 am - 6 pm Mon-Wed"`, `"10 pm- 6 am Daily"`, `"24 hrs Daily"`, `"8 AM - 2 AM, Daily"`.
+fn parse_hours(hours: &str) -> Result<Vec<EnforcementWindow>, String> {
+  let invalid = || format!("Unrecognized enforcement hours {hours:?}");
+  let captures = patterns().hours.captures(hours.trim()).ok_or_else(invalid)?;
+  let (start_minute, end_minute) = match (captures.get(1), captures.get(2)) {
+    (Some(from), Some(to)) => {
+      let start = parse_clock(from.as_str()).ok_or_else(invalid)?;
+      let end = parse_clock(to.as_str()).ok_or_else(invalid)?;
+      (start, if end <= start { end + MINUTES_PER_DAY } else { end })
+    }
+    _ => (0, MINUTES_PER_DAY),
+  };
+
+  Ok(parse_days(&captures[3])
+    .ok_or_else(invalid)?
+    .into_iter()
+    .map(|weekday| EnforcementWindow {
+      weekday,
+      start_minute,
+      end_minute,
+    })
+    .collect())
+}
+
+/// `"10 am"`, `"7:30 PM"` as minutes after midnight.
+fn parse_clock(clock: &str) -> Option<u32> {
+  let clock = clock.trim().to_ascii_lowercase();
+  let (time, pm) = match clock.strip_suffix("pm") {
+    Some(time) => (time, true),
+    None => (clock.strip_suffix("am")?, false),
+  };
+  let (hour, minute) = time.trim().split_once(':').unwrap_or((time.trim(), "0"));
+  let (hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+  if !(1..=12).contains(&hour) || minute >= 60 {
+    return None;
+  }
+  Some((hour % 12 + if pm { 12 } else { 0 }) * 60 + minute)
+}
+
+/// `"Daily"`, `"Mon-Fri"`, `"Sat"` or a comma-separated list of those.
+fn parse_days(days: &str) -> Option<Vec<Weekday>> {
+  let mut weekdays = Vec::new();
+  for part in days.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+    if part.eq_ignore_ascii_case("daily") {
+      weekdays.extend(WEEKDAYS);
+      continue;
+    }
+    let (first, last) = part.split_once('-').unwrap_or((part, part));
+    let first = first.trim().parse::<Weekday>().ok()?.num_days_from_monday() as usize;
+    let last = last.trim().parse::<Weekday>().ok()?.num_days_from_monday() as usize;
+    if first <= last {
+      weekdays.extend(&WEEKDAYS[first..=last]);
+    } else {
+      weekdays.extend(&WEEKDAYS[first..]);
+      weekdays.extend(&WEEKDAYS[..=last]);
+    }
+  }
+  weekdays.dedup();
+  (!weekdays.is_empty()).then_some(weekdays)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::calendar::EnforcementCalendar;
+  use crate::zones::ZoneCategory;
+  use chrono::NaiveDateTime;
+
+  fn at(value: &str) -> DateTime<Tz> {
+    calendar::local_datetime(NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap())
+  }
+
+  fn schedule(hours: &[&str], free_on_sundays: bool, free_on_holidays: bool) -> MeterSchedule {
+    MeterSchedule {
+      zone_type: "Test".to_string(),
+      hours: hours.iter().map(|hours| hours.to_string()).collect(),
+      free_on_sundays,
+      free_on_holidays,
+      provisional: false,
+      description: "Test schedule".to_string(),
+    }
+  }
+
+  fn window(weekday: Weekday, start_minute: u32, end_minute: u32) -> EnforcementWindow {
+    EnforcementWindow {
+      weekday,
+      start_minute,
+      end_minute,
+    }
+  }
+
+  /// Cost in cents and enforced minutes of a stay from `start` for `minutes`, in a zone without
+  /// overrides.
+  fn cost(tariff: &Tariff, start: &str, minutes: i64) -> (u32, u32) {
+    let calendar = EnforcementCalendar::load_bundled().unwrap();
+    let start = at(start);
+    let end = start + Duration::minutes(minutes);
+    let estimate = tariff.estimate(start, end, &calendar.for_zone("1", ZoneCategory::Paid));
+    (estimate.total_cents, estimate.enforced_minutes)
+  }
+
+  #[test]
+  fn clocks() {
+    let cases = [
+      ("10 am", Some(600)),
+      ("7:30 PM", Some(1170)),
+      ("12 am", Some(0)),
+      ("12 pm", Some(720)),
+      ("12:15am", Some(15)),
+      ("13 pm", None),
+      ("0 am", None),
+      ("10:60 am", None),
+      ("10", None),
+    ];
+    for (clock, expected) in cases {
+      assert_eq!(parse_clock(clock), expected, "{clock}");
+    }
+  }
+
+  #[test]
+  fn days() {
+    use Weekday::{Fri, Mon, Sat, Sun, Thu, Tue, Wed};
+    let cases: [(&str, Option<Vec<Weekday>>); 7] = [
+      ("Daily", Some(WEEKDAYS.to_vec())),
+      ("Mon-Wed", Some(vec![Mon, Tue, Wed])),
+      ("Sat", Some(vec![Sat])),
+      ("Fri-Mon", Some(vec![Fri, Sat, Sun, Mon])),
+      ("Mon, Thu-Fri", Some(vec![Mon, Thu, Fri])),
+      ("Funday", None),
+      ("", None),
+    ];
+    for (days, expected) in cases {
+      assert_eq!(parse_days(days), expected, "{days}");
+    }
+  }
+
+  #[test]
+  fn hours() {
+    let cases = [
+      ("10 am - 6 pm Mon-Wed", Some((3, 600, 1080))),
+      ("10 am - 9 pm Thu-Sat", Some((3, 600, 1260))),
+      // Overnight windows end past midnight of the day they start on.
+      ("10 pm - 6 am Daily", Some((7, 1320, 1800))),
+      ("10 pm- 6 am Daily", Some((7, 1320, 1800))),
+      ("8 AM - 2 AM, Daily", Some((7, 480, 1560))),
+      ("24 hrs Daily", Some((7, 0, 1440))),
+      ("whenever", None),
+      ("10 am - 6 pm Someday", None),
+    ];
+    for (hours, expected) in cases {
+      let windows = parse_hours(hours).ok();
+      let summary = windows.as_ref().map(|windows| (windows.len(), windows[0].start_minute, windows[0].end_minute));
+      assert_eq!(summary, expected, "{hours}");
+    }
+    assert_eq!(parse_hours("10 am - 6 pm Tue").unwrap(), vec![window(Weekday::Tue, 600, 1080)]);
+  }
+
+  #[test]
+  fn metered_tariffs() {
+    let downtown = schedule(&["10 am - 6 pm Mon-Wed", "10 am - 9 pm Thu-Sat"], true, true);
+    let cases = [
+      ("$2.75/hr", "3 hour limit", Some((275, None, Some(180)))),
+      ("$1/hr", "$8 daily max", Some((100, Some(800), None))),
+      ("$1.5 / hour", "30 minute limit", Some((150, None, Some(30)))),
+      ("$2/hr", "", Some((200, None, None))),
+      ("Free", "", None),
+      ("$2.755/hr", "", None),
+    ];
+    for (rate, label, expected) in cases {
+      let tariff = Tariff::metered(rate, label, &downtown).ok();
+      let summary = tariff
+        .as_ref()
+        .map(|tariff| (tariff.hourly_rate_cents, tariff.daily_max_cents, tariff.max_stay_minutes));
+      assert_eq!(summary, expected, "{rate} {label}");
+    }
+
+    let tariff = Tariff::metered("$2/hr", "", &downtown).unwrap();
+    assert_eq!(tariff.enforcement.len(), 6);
+    assert!(tariff.free_on_sundays && tariff.free_on_holidays && !tariff.permit_required);
+    assert!(Tariff::metered("$2/hr", "", &schedule(&["sometimes"], true, true)).is_err());
+  }
+
+  #[test]
+  fn residential_tariffs() {
+    let tariff = Tariff::residential(" 8 am - 5 pm Mon-Fri ").unwrap();
+    assert_eq!(tariff.hourly_rate_cents, 0);
+    assert!(tariff.permit_required && !tariff.provisional);
+    assert_eq!(tariff.enforcement.len(), 5);
+    assert_eq!(tariff.description, "Residential permit required 8 am - 5 pm Mon-Fri");
+    assert!(Tariff::residential("").is_err());
+  }
+
+  #[test]
+  fn stay_costs() {
+    let downtown =
+      Tariff::metered("$2/hr", "", &schedule(&["10 am - 6 pm Mon-Wed", "10 am - 9 pm Thu-Sat"], true, true)).unwrap();
+    let overnight = Tariff::metered("$1/hr", "", &schedule(&["10 pm - 6 am Daily"], false, false)).unwrap();
+    let late = Tariff::metered("$1/hr", "", &schedule(&["8 AM - 2 AM, Daily"], false, false)).unwrap();
+    let garage = Tariff::metered("$2/hr", "$8 daily max", &schedule(&["24 hrs Daily"], false, false)).unwrap();
+
+    let cases = [
+      // Monday, February 23, 2026: only the hour before 6 pm is paid.
+      (&downtown, "2026-02-23 17:00", 180, (200, 60)),
+      (&downtown, "2026-02-23 08:00", 60, (0, 0)),
+      // Thursday runs until 9 pm.
+      (&downtown, "2026-02-26 19:00", 60, (200, 60)),
+      // Free Sunday, and the observed and actual Independence Day 2026.
+      (&downtown, "2026-02-22 12:00", 120, (0, 0)),
+      (&downtown, "2026-07-03 12:00", 120, (0, 0)),
+      (&downtown, "2026-07-04 12:00", 120, (0, 0)),
+      // Overnight windows, including the one that started the evening before the stay.
+      (&overnight, "2026-02-23 23:00", 120, (200, 120)),
+      (&overnight, "2026-02-24 01:00", 120, (200, 120)),
+      (&overnight, "2026-02-24 05:00", 120, (100, 60)),
+      (&late, "2026-02-24 01:00", 120, (100, 60)),
+      // Daily maximum, applied per calendar day.
+      (&garage, "2026-02-23 08:00", 600, (800, 600)),
+      (&garage, "2026-02-23 20:00", 24 * 60, (1600, 24 * 60)),
+      // The 10 pm - 6 am window lasts 7 hours the night clocks spring forward, 9 the night they fall back.
+      (&overnight, "2026-03-07 22:00", 7 * 60, (700, 420)),
+      (&overnight, "2026-10-31 22:00", 9 * 60, (900, 540)),
+    ];
+    for (tariff, start, minutes, expected) in cases {
+      assert_eq!(cost(tariff, start, minutes), expected, "{start} for {minutes} min");
+    }
+  }
+
+  #[test]
+  fn dst_days_are_charged_by_elapsed_time() {
+    let hourly = Tariff::metered("$1/hr", "", &schedule(&["24 hrs Daily"], false, false)).unwrap();
+    let calendar = EnforcementCalendar::load_bundled().unwrap();
+    let calendar = calendar.for_zone("1", ZoneCategory::Paid);
+    for (day, next_day, hours) in [
+      ("2026-03-08 00:00", "2026-03-09 00:00", 23),
+      ("2026-11-01 00:00", "2026-11-02 00:00", 25),
+    ] {
+      let estimate = hourly.estimate(at(day), at(next_day), &calendar);
+      assert_eq!(estimate.total_cents, hours * 100, "{day}");
+      assert_eq!(estimate.enforced_minutes, hours * 60, "{day}");
+    }
+  }
+
+  #[test]
+  fn daily_max_is_flagged() {
+    let garage = Tariff::metered("$2/hr", "$8 daily max", &schedule(&["24 hrs Daily"], false, false)).unwrap();
+    let calendar = EnforcementCalendar::load_bundled().unwrap();
+    let calendar = calendar.for_zone("1", ZoneCategory::Paid);
+    let start = at("2026-02-23 08:00");
+    assert!(garage.estimate(start, start + Duration::hours(5), &calendar).daily_max_applied);
+    assert!(!garage.estimate(start, start + Duration::hours(4), &calendar).daily_max_applied);
+  }
+
+  #[test]
+  fn violations() {
+    let limited = Tariff::metered("$2/hr", "2 hour limit", &schedule(&["10 am - 6 pm Mon-Sat"], true, true)).unwrap();
+    let residential = Tariff::residential("8 am - 5 pm Mon-Fri").unwrap();
+    let calendar = EnforcementCalendar::load_bundled().unwrap();
+    let kinds = |tariff: &Tariff, zone: &str, start: &str, minutes: i64| {
+      let start = at(start);
+      let end = start + Duration::minutes(minutes);
+      let estimate = tariff.estimate(start, end, &calendar.for_zone(zone, ZoneCategory::Paid));
+      estimate.violations.iter().map(|violation| violation.kind).collect::<Vec<_>>()
+    };
+
+    assert_eq!(kinds(&limited, "1", "2026-02-23 12:00", 180), [ViolationKind::MaxStay]);
+    assert!(kinds(&limited, "1", "2026-02-23 12:00", 120).is_empty());
+    // Only the enforced part counts toward the limit.
+    assert!(kinds(&limited, "1", "2026-02-23 16:30", 240).is_empty());
+    assert_eq!(kinds(&residential, "1", "2026-02-23 16:00", 60), [ViolationKind::PermitRequired]);
+    assert!(kinds(&residential, "1", "2026-02-23 18:00", 60).is_empty());
+    // The bundled calendar closes zone 80511 for the holiday parade.
+    assert_eq!(kinds(&limited, "80511", "2026-12-04 15:00", 120), [ViolationKind::Closed]);
+  }
+
+  #[test]
+  fn status() {
+    let downtown = Tariff::metered("$2/hr", "", &schedule(&["10 am - 6 pm Mon-Sat"], true, true)).unwrap();
+    let calendar = EnforcementCalendar::load_bundled().unwrap();
+    let calendar = calendar.for_zone("1", ZoneCategory::Paid);
+
+    let status = downtown.status_at(at("2026-02-22 12:00"), &calendar);
+    assert!(!status.enforced);
+    assert_eq!(status.reason, "Free on Sundays.");
+    assert_eq!(status.changes_at, Some(at("2026-02-23 10:00")));
+
+    let status = downtown.status_at(at("2026-02-23 12:00"), &calendar);
+    assert!(status.enforced);
+    assert_eq!(status.changes_at, Some(at("2026-02-23 18:00")));
+  }
+}
@@ -0,0 +1,652 @@
+use crate::error::{CommandError, CommandResult};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+/// `MIGRATIONS[n]` upgrades the database from `PRAGMA user_version` `n` to `n + 1`. Append only.
+const MIGRATIONS: &[&str] = &["
+  CREATE TABLE parking_sessions (
+    id TEXT PRIMARY KEY,
+    status TEXT NOT NULL
+      CHECK (status IN ('captured', 'active', 'renewed', 'expired', 'cancelled')),
+    parked_lat REAL NOT NULL,
+    parked_lng REAL NOT NULL,
+    parked_accuracy_meters REAL,
+    captured_zone_number TEXT,
+    captured_rate TEXT,
+    captured_category TEXT NOT NULL DEFAULT 'none'
+      CHECK (captured_category IN ('paid', 'residential', 'none')),
+    confirmed_zone_number TEXT,
+    duration_minutes INTEGER CHECK (duration_minutes IS NULL OR duration_minutes > 0),
+    starts_at TEXT,
+    expires_at TEXT,
+    renew_parent_session_id TEXT REFERENCES parking_sessions(id),
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+  );
+
+  CREATE INDEX parking_sessions_created_idx ON parking_sessions(created_at DESC);
+  CREATE INDEX parking_sessions_status_idx ON parking_sessions(status, expires_at);
+", "
+  CREATE TABLE parking_notifications (
+    id INTEGER PRIMARY KEY,
+    parking_session_id TEXT NOT NULL REFERENCES parking_sessions(id) ON DELETE CASCADE,
+    notification_type TEXT NOT NULL
+      CHECK (notification_type IN ('payment_confirmed', 'post_payment_info', 'renew_reminder', 'parking_expired')),
+    scheduled_at TEXT NOT NULL,
+    sent_at TEXT,
+    status TEXT NOT NULL DEFAULT 'queued'
+      CHECK (status IN ('queued', 'sent', 'failed', 'skipped')),
+    attempt_count INTEGER NOT NULL DEFAULT 0 CHECK (attempt_count >= 0),
+    last_error TEXT,
+    message_text TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (parking_session_id, notification_type)
+  );
+
+  CREATE INDEX parking_notifications_due_idx ON parking_notifications(status, scheduled_at);
+", "
+  ALTER TABLE parking_sessions ADD COLUMN resume_token TEXT;
+  UPDATE parking_sessions SET resume_token = lower(hex(randomblob(16))) WHERE resume_token IS NULL;
+  CREATE UNIQUE INDEX parking_sessions_resume_token_idx ON parking_sessions(resume_token);
+"];
+
+const SESSION_COLUMNS: &str = "id, status, parked_lat, parked_lng, parked_accuracy_meters, captured_zone_number, \
+  captured_rate, captured_category, confirmed_zone_number, duration_minutes, starts_at, expires_at, \
+  resume_token, renew_parent_session_id, created_at, updated_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStatus {
+  Captured,
+  Active,
+  Renewed,
+  Expired,
+  Cancelled,
+}
+
+impl SessionStatus {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      SessionStatus::Captured => "captured",
+      SessionStatus::Active => "active",
+      SessionStatus::Renewed => "renewed",
+      SessionStatus::Expired => "expired",
+      SessionStatus::Cancelled => "cancelled",
+    }
+  }
+
+  fn parse(value: &str) -> Option<Self> {
+    match value {
+      "captured" => Some(SessionStatus::Captured),
+      "active" => Some(SessionStatus::Active),
+      "renewed" => Some(SessionStatus::Renewed),
+      "expired" => Some(SessionStatus::Expired),
+      "cancelled" => Some(SessionStatus::Cancelled),
+      _ => None,
+    }
+  }
+}
+
+/// Local mirror of a `parking_sessions` row in Supabase, without the account and SMS columns.
+/// Timestamps are RFC 3339 UTC strings.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParkingSession {
+  pub id: String,
+  pub status: SessionStatus,
+  pub parked_lat: f64,
+  pub parked_lng: f64,
+  pub parked_accuracy_meters: Option<f64>,
+  pub captured_zone_number: Option<String>,
+  pub captured_rate: Option<String>,
+  pub captured_category: String,
+  pub confirmed_zone_number: Option<String>,
+  pub duration_minutes: Option<u32>,
+  pub starts_at: Option<String>,
+  pub expires_at: Option<String>,
+  /// Secret for `parkos://resume?token=…` links, like `resume_token` in Supabase.
+  pub resume_token: String,
+  pub renew_parent_session_id: Option<String>,
+  pub created_at: String,
+  pub updated_at: String,
+}
+
+pub struct NewSession<'a> {
+  pub id: &'a str,
+  pub status: SessionStatus,
+  pub parked_lat: f64,
+  pub parked_lng: f64,
+  pub parked_accuracy_meters: Option<f64>,
+  pub captured_zone_number: Option<&'a str>,
+  pub captured_rate: Option<&'a str>,
+  pub captured_category: &'a str,
+  pub resume_token: &'a str,
+  pub renew_parent_session_id: Option<&'a str>,
+  pub now: &'a str,
+}
+
+pub struct Activation<'a> {
+  pub zone_number: &'a str,
+  pub duration_minutes: u32,
+  pub starts_at: &'a str,
+  pub expires_at: &'a str,
+}
+
+pub fn open(path: &std::path::Path) -> CommandResult<Connection> {
+  let mut connection =
+    Connection::open(path).map_err(|error| CommandError::database("Failed to open parking session database", error))?;
+  connection
+    .execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON; PRAGMA busy_timeout = 5000;")
+    .map_err(|error| CommandError::database("Failed to configure parking session database", error))?;
+  migrate(&mut connection)?;
+  Ok(connection)
+}
+
+/// Schema version this build migrates databases to.
+pub fn supported_schema_version() -> usize {
+  MIGRATIONS.len()
+}
+
+pub fn schema_version(connection: &Connection) -> CommandResult<usize> {
+  connection
+    .query_row("PRAGMA user_version", [], |row| row.get(0))
+    .map_err(|error| CommandError::database("Failed to read parking session database version", error))
+}
+
+fn migrate(connection: &mut Connection) -> CommandResult<()> {
+  let version = schema_version(connection)?;
+
+  if version > MIGRATIONS.len() {
+    return Err(CommandError::Unsupported(format!(
+      "Parking session database uses schema version {version}, which is newer than this app supports ({})",
+      MIGRATIONS.len()
+    )));
+  }
+
+  for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+    let to_version = index + 1;
+    let transaction = connection
+      .transaction()
+      .map_err(|error| CommandError::database("Failed to migrate parking session database", error))?;
+    transaction
+      .execute_batch(migration)
+      .and_then(|_| transaction.pragma_update(None, "user_version", to_version))
+      .and_then(|_| transaction.commit())
+      .map_err(|error| {
+        CommandError::database(
+          &format!("Failed to migrate parking session database to schema version {to_version}"),
+          error,
+        )
+      })?;
+    log::info!("Migrated parking session database to schema version {to_version}");
+  }
+
+  Ok(())
+}
+
+fn session_from_row(row: &Row<'_>) -> rusqlite::Result<ParkingSession> {
+  let status_index = row.as_ref().column_index("status")?;
+  let status: String = row.get(status_index)?;
+  let status = SessionStatus::parse(&status).ok_or_else(|| {
+    rusqlite::Error::FromSqlConversionFailure(
+      status_index,
+      rusqlite::types::Type::Text,
+      format!("unknown parking session status {status:?}").into(),
+    )
+  })?;
+
+  Ok(ParkingSession {
+    id: row.get("id")?,
+    status,
+    parked_lat: row.get("parked_lat")?,
+    parked_lng: row.get("parked_lng")?,
+    parked_accuracy_meters: row.get("parked_accuracy_meters")?,
+    captured_zone_number: row.get("captured_zone_number")?,
+    captured_rate: row.get("captured_rate")?,
+    captured_category: row.get("captured_category")?,
+    confirmed_zone_number: row.get("confirmed_zone_number")?,
+    duration_minutes: row.get("duration_minutes")?,
+    starts_at: row.get("starts_at")?,
+    expires_at: row.get("expires_at")?,
+    resume_token: row.get("resume_token")?,
+    renew_parent_session_id: row.get("renew_parent_session_id")?,
+    created_at: row.get("created_at")?,
+    updated_at: row.get("updated_at")?,
+  })
+}
+
+pub fn insert_session(connection: &Connection, session: &NewSession<'_>) -> CommandResult<()> {
+  connection
+    .execute(
+      "INSERT INTO parking_sessions (id, status, parked_lat, parked_lng, parked_accuracy_meters, \
+       captured_zone_number, captured_rate, captured_category, resume_token, renew_parent_session_id, created_at, \
+       updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11)",
+      params![
+        session.id,
+        session.status.as_str(),
+        session.parked_lat,
+        session.parked_lng,
+        session.parked_accuracy_meters,
+        session.captured_zone_number,
+        session.captured_rate,
+        session.captured_category,
+        session.resume_token,
+        session.renew_parent_session_id,
+        session.now,
+      ],
+    )
+    .map_err(|error| CommandError::database("Failed to save parking session", error))?;
+  Ok(())
+}
+
+pub fn find_session(connection: &Connection, id: &str) -> CommandResult<ParkingSession> {
+  connection
+    .query_row(
+      &format!("SELECT {SESSION_COLUMNS} FROM parking_sessions WHERE id = ?1"),
+      [id],
+      session_from_row,
+    )
+    .optional()
+    .map_err(|error| CommandError::database("Failed to read parking session", error))?
+    .ok_or_else(|| CommandError::NotFound(format!("Parking session {id} does not exist")))
+}
+
+pub fn find_session_by_resume_token(connection: &Connection, resume_token: &str) -> CommandResult<ParkingSession> {
+  connection
+    .query_row(
+      &format!("SELECT {SESSION_COLUMNS} FROM parking_sessions WHERE resume_token = ?1"),
+      [resume_token],
+      session_from_row,
+    )
+    .optional()
+    .map_err(|error| CommandError::database("Failed to read parking session", error))?
+    .ok_or_else(|| CommandError::NotFound("Parking session not found for this resume token".to_string()))
+}
+
+pub fn list_sessions(connection: &Connection, limit: u32) -> CommandResult<Vec<ParkingSession>> {
+  let mut statement = connection
+    .prepare(&format!(
+      "SELECT {SESSION_COLUMNS} FROM parking_sessions ORDER BY created_at DESC, rowid DESC LIMIT ?1"
+    ))
+    .map_err(|error| CommandError::database("Failed to list parking sessions", error))?;
+  let sessions = statement
+    .query_map([limit], session_from_row)
+    .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+    .map_err(|error| CommandError::database("Failed to list parking sessions", error))?;
+  Ok(sessions)
+}
+
+/// The most recently started session whose meter is still running.
+pub fn running_session(connection: &Connection, now: &str) -> CommandResult<Option<ParkingSession>> {
+  connection
+    .query_row(
+      &format!(
+        "SELECT {SESSION_COLUMNS} FROM parking_sessions WHERE status = 'active' AND expires_at > ?1 \
+         ORDER BY starts_at DESC, rowid DESC LIMIT 1"
+      ),
+      [now],
+      session_from_row,
+    )
+    .optional()
+    .map_err(|error| CommandError::database("Failed to read active parking session", error))
+}
+
+pub fn activate_session(
+  connection: &Connection,
+  id: &str,
+  activation: &Activation<'_>,
+  now: &str,
+) -> CommandResult<()> {
+  connection
+    .execute(
+      "UPDATE parking_sessions SET status = 'active', confirmed_zone_number = ?2, duration_minutes = ?3, \
+       starts_at = ?4, expires_at = ?5, updated_at = ?6 WHERE id = ?1",
+      params![
+        id,
+        activation.zone_number,
+        activation.duration_minutes,
+        activation.starts_at,
+        activation.expires_at,
+        now
+      ],
+    )
+    .map_err(|error| CommandError::database("Failed to activate parking session", error))?;
+  Ok(())
+}
+
+pub fn set_status(connection: &Connection, id: &str, status: SessionStatus, now: &str) -> CommandResult<()> {
+  connection
+    .execute(
+      "UPDATE parking_sessions SET status = ?2, updated_at = ?3 WHERE id = ?1",
+      params![id, status.as_str(), now],
+    )
+    .map_err(|error| CommandError::database("Failed to update parking session", error))?;
+  Ok(())
+}
+
+/// Marks active sessions whose meter ran out as expired, like the `parking-agent-tick` job does.
+pub fn expire_due_sessions(connection: &Connection, now: &str) -> CommandResult<usize> {
+  connection
+    .execute(
+      "UPDATE parking_sessions SET status = 'expired', updated_at = ?1 \
+       WHERE status = 'active' AND expires_at IS NOT NULL AND expires_at <= ?1",
+      [now],
+    )
+    .map_err(|error| CommandError::database("Failed to expire parking sessions", error))
+}
+
+/// A queued reminder joined with the session it belongs to.
+pub struct DueNotification {
+  pub id: i64,
+  pub notification_type: String,
+  pub session: ParkingSession,
+}
+
+/// Queues (or re-queues, when the session is activated again) one reminder per `(type, scheduled_at)`.
+pub fn queue_notifications(
+  connection: &Connection,
+  session_id: &str,
+  schedule: &[(&str, String)],
+  now: &str,
+) -> CommandResult<()> {
+  let mut statement = connection
+    .prepare(
+      "INSERT INTO parking_notifications (parking_session_id, notification_type, scheduled_at, created_at, updated_at) \
+       VALUES (?1, ?2, ?3, ?4, ?4) \
+       ON CONFLICT (parking_session_id, notification_type) DO UPDATE SET scheduled_at = excluded.scheduled_at, \
+       status = 'queued', sent_at = NULL, attempt_count = 0, last_error = NULL, message_text = NULL, \
+       updated_at = excluded.updated_at",
+    )
+    .map_err(|error| CommandError::database("Failed to queue parking reminders", error))?;
+  for (notification_type, scheduled_at) in schedule {
+    statement
+      .execute(params![session_id, notification_type, scheduled_at, now])
+      .map_err(|error| CommandError::database("Failed to queue parking reminders", error))?;
+  }
+  Ok(())
+}
+
+/// Skips the session's queued reminders, or only those of `types` when given.
+pub fn skip_pending_notifications(
+  connection: &Connection,
+  session_id: &str,
+  types: Option<&[&str]>,
+  reason: &str,
+  now: &str,
+) -> CommandResult<()> {
+  let skip = |notification_type: Option<&str>| {
+    connection.execute(
+      "UPDATE parking_notifications SET status = 'skipped', last_error = ?3, updated_at = ?4 \
+       WHERE parking_session_id = ?1 AND status = 'queued' AND (?2 IS NULL OR notification_type = ?2)",
+      params![session_id, notification_type, reason, now],
+    )
+  };
+
+  match types {
+    Some(types) => types.iter().try_for_each(|notification_type| skip(Some(*notification_type)).map(|_| ())),
+    None => skip(None).map(|_| ()),
+  }
+  .map_err(|error| CommandError::database("Failed to cancel parking reminders", error))
+}
+
+pub fn due_notifications(connection: &Connection, now: &str) -> CommandResult<Vec<DueNotification>> {
+  let columns = SESSION_COLUMNS
+    .split(", ")
+    .map(|column| format!("s.{column}"))
+    .collect::<Vec<_>>()
+    .join(", ");
+  let mut statement = connection
+    .prepare(&format!(
+      "SELECT n.id AS notification_id, n.notification_type, {columns} FROM parking_notifications n \
+       JOIN parking_sessions s ON s.id = n.parking_session_id \
+       WHERE n.status = 'queued' AND n.scheduled_at <= ?1 ORDER BY n.scheduled_at, n.id"
+    ))
+    .map_err(|error| CommandError::database("Failed to read due parking reminders", error))?;
+  let due = statement
+    .query_map([now], |row| {
+      Ok(DueNotification {
+        id: row.get("notification_id")?,
+        notification_type: row.get("notification_type")?,
+        session: session_from_row(row)?,
+      })
+    })
+    .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+    .map_err(|error| CommandError::database("Failed to read due parking reminders", error))?;
+  Ok(due)
+}
+
+/// When the earliest queued reminder is due, if any.
+pub fn next_notification_at(connection: &Connection) -> CommandResult<Option<String>> {
+  connection
+    .query_row(
+      "SELECT MIN(scheduled_at) FROM parking_notifications WHERE status = 'queued'",
+      [],
+      |row| row.get(0),
+    )
+    .map_err(|error| CommandError::database("Failed to read parking reminders", error))
+}
+
+pub fn mark_notification_sent(connection: &Connection, id: i64, message_text: &str, now: &str) -> CommandResult<()> {
+  connection
+    .execute(
+      "UPDATE parking_notifications SET status = 'sent', sent_at = ?3, message_text = ?2, \
+       attempt_count = attempt_count + 1, updated_at = ?3 WHERE id = ?1",
+      params![id, message_text, now],
+    )
+    .map_err(|error| CommandError::database("Failed to update parking reminder", error))?;
+  Ok(())
+}
+
+pub fn mark_notification_skipped(connection: &Connection, id: i64, reason: &str, now: &str) -> CommandResult<()> {
+  connection
+    .execute(
+      "UPDATE parking_notifications SET status = 'skipped', last_error = ?2, updated_at = ?3 WHERE id = ?1",
+      params![id, reason, now],
+    )
+    .map_err(|error| CommandError::database("Failed to update parking reminder", error))?;
+  Ok(())
+}
+
+pub fn mark_notification_failed(connection: &Connection, id: i64, error_message: &str, now: &str) -> CommandResult<()> {
+  connection
+    .execute(
+      "UPDATE parking_notifications SET status = 'failed', last_error = ?2, attempt_count = attempt_count + 1, \
+       updated_at = ?3 WHERE id = ?1",
+      params![id, error_message, now],
+    )
+    .map_err(|error| CommandError::database("Failed to update parking reminder", error))?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn connection() -> Connection {
+    let mut connection = Connection::open_in_memory().unwrap();
+    connection.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+    migrate(&mut connection).unwrap();
+    connection
+  }
+
+  fn insert(connection: &Connection, id: &str, resume_token: &str, now: &str) {
+    insert_session(
+      connection,
+      &NewSession {
+        id,
+        status: SessionStatus::Captured,
+        parked_lat: 35.28,
+        parked_lng: -120.66,
+        parked_accuracy_meters: Some(8.0),
+        captured_zone_number: Some("80511"),
+        captured_rate: Some("$2.75/hr"),
+        captured_category: "paid",
+        resume_token,
+        renew_parent_session_id: None,
+        now,
+      },
+    )
+    .unwrap();
+  }
+
+  fn activate(connection: &Connection, id: &str, starts_at: &str, expires_at: &str) {
+    let activation = Activation {
+      zone_number: "80511",
+      duration_minutes: 60,
+      starts_at,
+      expires_at,
+    };
+    activate_session(connection, id, &activation, starts_at).unwrap();
+  }
+
+  fn notification_statuses(connection: &Connection, session_id: &str) -> Vec<(String, String)> {
+    let mut statement = connection
+      .prepare(
+        "SELECT notification_type, status FROM parking_notifications WHERE parking_session_id = ?1 \
+         ORDER BY notification_type",
+      )
+      .unwrap();
+    let rows = statement.query_map([session_id], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+    rows.collect::<rusqlite::Result<_>>().unwrap()
+  }
+
+  #[test]
+  fn migrates_a_new_database_to_the_latest_version() {
+    let connection = connection();
+    assert_eq!(schema_version(&connection).unwrap(), supported_schema_version());
+    insert(&connection, "session-1", "token-1", "2026-02-23T20:00:00Z");
+    assert_eq!(find_session(&connection, "session-1").unwrap().resume_token, "token-1");
+  }
+
+  #[test]
+  fn migration_is_idempotent() {
+    let mut connection = connection();
+    insert(&connection, "session-1", "token-1", "2026-02-23T20:00:00Z");
+    migrate(&mut connection).unwrap();
+    assert_eq!(schema_version(&connection).unwrap(), supported_schema_version());
+    assert_eq!(list_sessions(&connection, 10).unwrap().len(), 1);
+  }
+
+  #[test]
+  fn resume_token_migration_fills_existing_rows() {
+    let mut connection = Connection::open_in_memory().unwrap();
+    connection.execute_batch(MIGRATIONS[0]).unwrap();
+    connection.execute_batch(MIGRATIONS[1]).unwrap();
+    connection.pragma_update(None, "user_version", 2).unwrap();
+    for id in ["old-1", "old-2"] {
+      connection
+        .execute(
+          "INSERT INTO parking_sessions (id, status, parked_lat, parked_lng, created_at, updated_at) \
+           VALUES (?1, 'expired', 35.28, -120.66, '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z')",
+          [id],
+        )
+        .unwrap();
+    }
+
+    migrate(&mut connection).unwrap();
+    assert_eq!(schema_version(&connection).unwrap(), 3);
+    let tokens: Vec<String> = list_sessions(&connection, 10)
+      .unwrap()
+      .into_iter()
+      .map(|session| session.resume_token)
+      .collect();
+    assert_eq!(tokens.len(), 2);
+    assert_ne!(tokens[0], tokens[1]);
+    assert!(tokens
+      .iter()
+      .all(|token| token.len() == 32 && token.chars().all(|c| c.is_ascii_hexdigit())));
+    assert_eq!(find_session_by_resume_token(&connection, &tokens[0]).unwrap().status, SessionStatus::Expired);
+  }
+
+  #[test]
+  fn newer_databases_are_refused() {
+    let mut connection = Connection::open_in_memory().unwrap();
+    connection
+      .pragma_update(None, "user_version", supported_schema_version() + 1)
+      .unwrap();
+    assert!(matches!(migrate(&mut connection), Err(CommandError::Unsupported(_))));
+  }
+
+  #[test]
+  fn status_transitions() {
+    let connection = connection();
+    insert(&connection, "session-1", "token-1", "2026-02-23T20:00:00Z");
+    assert_eq!(find_session(&connection, "session-1").unwrap().status, SessionStatus::Captured);
+    assert!(running_session(&connection, "2026-02-23T20:00:00Z").unwrap().is_none());
+
+    activate(&connection, "session-1", "2026-02-23T20:00:00Z", "2026-02-23T21:00:00Z");
+    let session = find_session(&connection, "session-1").unwrap();
+    assert_eq!(session.status, SessionStatus::Active);
+    assert_eq!(session.confirmed_zone_number.as_deref(), Some("80511"));
+    assert_eq!(session.duration_minutes, Some(60));
+    assert_eq!(running_session(&connection, "2026-02-23T20:30:00Z").unwrap().unwrap().id, "session-1");
+
+    assert_eq!(expire_due_sessions(&connection, "2026-02-23T20:59:59Z").unwrap(), 0);
+    assert_eq!(expire_due_sessions(&connection, "2026-02-23T21:00:00Z").unwrap(), 1);
+    assert_eq!(find_session(&connection, "session-1").unwrap().status, SessionStatus::Expired);
+    assert!(running_session(&connection, "2026-02-23T21:00:00Z").unwrap().is_none());
+
+    set_status(&connection, "session-1", SessionStatus::Renewed, "2026-02-23T21:05:00Z").unwrap();
+    let session = find_session(&connection, "session-1").unwrap();
+    assert_eq!(session.status, SessionStatus::Renewed);
+    assert_eq!(session.updated_at, "2026-02-23T21:05:00Z");
+  }
+
+  #[test]
+  fn unknown_statuses_are_rejected() {
+    let connection = connection();
+    insert(&connection, "session-1", "token-1", "2026-02-23T20:00:00Z");
+    let result = connection.execute("UPDATE parking_sessions SET status = 'parked' WHERE id = 'session-1'", []);
+    assert!(result.is_err());
+    assert!(matches!(find_session(&connection, "missing"), Err(CommandError::NotFound(_))));
+  }
+
+  #[test]
+  fn reminders_follow_the_session() {
+    let connection = connection();
+    insert(&connection, "session-1", "token-1", "2026-02-23T20:00:00Z");
+    activate(&connection, "session-1", "2026-02-23T20:00:00Z", "2026-02-23T21:00:00Z");
+    let schedule = [
+      ("payment_confirmed", "2026-02-23T20:00:00Z".to_string()),
+      ("renew_reminder", "2026-02-23T20:50:00Z".to_string()),
+      ("parking_expired", "2026-02-23T21:00:00Z".to_string()),
+    ];
+    queue_notifications(&connection, "session-1", &schedule, "2026-02-23T20:00:00Z").unwrap();
+    assert_eq!(next_notification_at(&connection).unwrap().as_deref(), Some("2026-02-23T20:00:00Z"));
+
+    let due = due_notifications(&connection, "2026-02-23T20:00:00Z").unwrap();
+    assert_eq!(due.len(), 1);
+    mark_notification_sent(&connection, due[0].id, "Payment confirmed", "2026-02-23T20:00:05Z").unwrap();
+
+    skip_pending_notifications(
+      &connection,
+      "session-1",
+      Some(&["renew_reminder"]),
+      "Renewed.",
+      "2026-02-23T20:10:00Z",
+    )
+    .unwrap();
+    assert_eq!(
+      notification_statuses(&connection, "session-1"),
+      [
+        ("parking_expired".to_string(), "queued".to_string()),
+        ("payment_confirmed".to_string(), "sent".to_string()),
+        ("renew_reminder".to_string(), "skipped".to_string()),
+      ]
+    );
+
+    // Activating again re-queues every reminder.
+    queue_notifications(&connection, "session-1", &schedule, "2026-02-23T20:20:00Z").unwrap();
+    assert!(notification_statuses(&connection, "session-1")
+      .iter()
+      .all(|(_, status)| status == "queued"));
+
+    let cancelled_at = "2026-02-23T20:30:00Z";
+    skip_pending_notifications(&connection, "session-1", None, "Session was cancelled.", cancelled_at).unwrap();
+    assert!(notification_statuses(&connection, "session-1")
+      .iter()
+      .all(|(_, status)| status == "skipped"));
+    assert_eq!(next_notification_at(&connection).unwrap(), None);
+  }
+}
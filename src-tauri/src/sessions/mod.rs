@@ -0,0 +1,434 @@
+mod db;
+mod reminders;
+
+use crate::error::{CommandError, CommandResult};
+use crate::payment_profile::FieldError;
+use crate::paths;
+use crate::zones::{self, ZoneCategory, ZoneIndex};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+
+pub use db::{ParkingSession, SessionStatus};
+pub use reminders::start as start_reminders;
+
+const DEFAULT_HISTORY_LIMIT: u32 = 50;
+const MAX_HISTORY_LIMIT: u32 = 500;
+const MAX_RESUME_TOKEN_LENGTH: usize = 128;
+
+/// Same fallback as `FALLBACK_RATE_BY_CATEGORY` in `/api/parking/session/capture`.
+fn fallback_rate(category: ZoneCategory) -> Option<&'static str> {
+  match category {
+    ZoneCategory::Paid => Some("$2.75/hr"),
+    ZoneCategory::Residential => Some("Permit required"),
+    ZoneCategory::None => None,
+  }
+}
+
+/// SQLite database of parking sessions in the app data dir. The connection is opened on first use
+/// so a wipe can close it and the next command starts from an empty database.
+pub struct SessionStore {
+  path: PathBuf,
+  connection: Mutex<Option<Connection>>,
+  /// Wakes the reminder thread when the schedule changes.
+  reminders: Mutex<Option<Sender<()>>>,
+  /// Set for the whole of a wipe; the database is not reopened until it clears.
+  suspended: AtomicBool,
+}
+
+/// Returned by `SessionStore::suspend`; lets the store reopen the database when dropped.
+pub struct Suspended<'a>(&'a SessionStore);
+
+impl Drop for Suspended<'_> {
+  fn drop(&mut self) {
+    self.0.suspended.store(false, Ordering::SeqCst);
+    self.0.wake_reminders();
+  }
+}
+
+impl SessionStore {
+  pub fn new(app: &tauri::AppHandle) -> CommandResult<Self> {
+    Ok(SessionStore {
+      path: paths::app_data_file(app, "parking_sessions.sqlite3")?,
+      connection: Mutex::new(None),
+      reminders: Mutex::new(None),
+      suspended: AtomicBool::new(false),
+    })
+  }
+
+  fn with_connection<T>(&self, action: impl FnOnce(&mut Connection) -> CommandResult<T>) -> CommandResult<T> {
+    let mut guard = self
+      .connection
+      .lock()
+      .map_err(|_| CommandError::Internal("Parking session database lock is poisoned".to_string()))?;
+    // Checked under the lock, so nothing opens the database between `suspend` closing it and the
+    // guard being dropped.
+    if self.suspended.load(Ordering::SeqCst) {
+      return Err(CommandError::Conflict("Parking sessions are unavailable while local data is wiped".to_string()));
+    }
+
+    if guard.is_none() {
+      if let Some(parent) = self.path.parent() {
+        fs::create_dir_all(parent)
+          .map_err(|error| CommandError::io("Failed to create parking session directory", error))?;
+      }
+      *guard = Some(db::open(&self.path)?);
+    }
+
+    match guard.as_mut() {
+      Some(connection) => action(connection),
+      None => Err(CommandError::Internal("Parking session database is not open".to_string())),
+    }
+  }
+
+  fn set_waker(&self, sender: Sender<()>) {
+    if let Ok(mut guard) = self.reminders.lock() {
+      *guard = Some(sender);
+    }
+  }
+
+  /// Makes the reminder thread re-read the schedule instead of sleeping until its old deadline.
+  fn wake_reminders(&self) {
+    if let Ok(guard) = self.reminders.lock() {
+      if let Some(sender) = guard.as_ref() {
+        let _ = sender.send(());
+      }
+    }
+  }
+
+  /// `(current, supported)` schema versions of the database.
+  pub fn schema_versions(&self) -> CommandResult<(usize, usize)> {
+    self.with_connection(|connection| Ok((db::schema_version(connection)?, db::supported_schema_version())))
+  }
+
+  /// Closes the database and keeps it closed, for the reminder thread too, until the returned guard
+  /// is dropped.
+  pub fn suspend(&self) -> Suspended<'_> {
+    self.suspended.store(true, Ordering::SeqCst);
+    if let Ok(mut guard) = self.connection.lock() {
+      guard.take();
+    }
+    Suspended(self)
+  }
+}
+
+fn now() -> chrono::DateTime<chrono::Utc> {
+  chrono::Utc::now()
+}
+
+fn timestamp(value: chrono::DateTime<chrono::Utc>) -> String {
+  value.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+fn new_resume_token() -> String {
+  uuid::Uuid::new_v4().simple().to_string()
+}
+
+fn validate_activation(zone_number: &str, duration_minutes: u32) -> CommandResult<String> {
+  let zone_number = zone_number.trim();
+  let mut fields = Vec::new();
+  if zone_number.is_empty() {
+    fields.push(FieldError {
+      field: "zoneNumber".to_string(),
+      message: "Zone number is required.".to_string(),
+    });
+  }
+  if duration_minutes == 0 {
+    fields.push(FieldError {
+      field: "durationMinutes".to_string(),
+      message: "Duration must be at least one minute.".to_string(),
+    });
+  }
+
+  if fields.is_empty() {
+    Ok(zone_number.to_string())
+  } else {
+    Err(CommandError::Validation { fields })
+  }
+}
+
+/// Start and expiry timestamps, plus the reminders to queue for that window.
+fn activation_window(duration_minutes: u32) -> (String, String, Vec<(&'static str, String)>) {
+  let starts_at = now();
+  let expires_at = starts_at + chrono::Duration::minutes(i64::from(duration_minutes));
+  let reminders = reminders::schedule(starts_at, expires_at, duration_minutes);
+  (timestamp(starts_at), timestamp(expires_at), reminders)
+}
+
+/// Records where the car was parked and which zone it was in, before anything is paid.
+#[tauri::command]
+pub fn capture_parking_session(
+  zones: tauri::State<'_, ZoneIndex>,
+  sessions: tauri::State<'_, SessionStore>,
+  lat: f64,
+  lng: f64,
+  accuracy_meters: Option<f64>,
+) -> CommandResult<ParkingSession> {
+  zones::validate_coordinate(lat, lng)?;
+  let accuracy_meters = accuracy_meters.filter(|accuracy| accuracy.is_finite() && *accuracy >= 0.0);
+  let zone = zones::resolve_current_zone(&zones, lat, lng);
+  let captured_rate = zone.rate.as_deref().or(fallback_rate(zone.category));
+
+  sessions.with_connection(|connection| {
+    let id = uuid::Uuid::new_v4().to_string();
+    db::insert_session(
+      connection,
+      &db::NewSession {
+        id: &id,
+        status: SessionStatus::Captured,
+        parked_lat: lat,
+        parked_lng: lng,
+        parked_accuracy_meters: accuracy_meters,
+        captured_zone_number: zone.zone_number.as_deref(),
+        captured_rate,
+        captured_category: zone.category.as_str(),
+        resume_token: &new_resume_token(),
+        renew_parent_session_id: None,
+        now: &timestamp(now()),
+      },
+    )?;
+    db::find_session(connection, &id)
+  })
+}
+
+/// Same shape as the `session` returned by `/api/parking/session/resume`. Rules rundowns are not
+/// stored locally, so `rulesRundown` is always null.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumedParkingSession {
+  pub id: String,
+  pub status: SessionStatus,
+  pub lat: f64,
+  pub lng: f64,
+  pub accuracy_meters: Option<f64>,
+  pub zone_number: Option<String>,
+  pub captured_rate: Option<String>,
+  pub duration_minutes: Option<u32>,
+  pub starts_at: Option<String>,
+  pub expires_at: Option<String>,
+  pub is_expired: bool,
+  pub rules_rundown: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResumeParkingSessionResponse {
+  pub ok: bool,
+  pub session: ResumedParkingSession,
+}
+
+/// Looks up the session a resume link points to.
+pub fn resume(sessions: &SessionStore, token: &str) -> CommandResult<ResumedParkingSession> {
+  let token = token.trim();
+  if token.is_empty() || token.len() > MAX_RESUME_TOKEN_LENGTH {
+    return Err(CommandError::Validation {
+      fields: vec![FieldError {
+        field: "token".to_string(),
+        message: "Resume token is missing or malformed.".to_string(),
+      }],
+    });
+  }
+
+  let now = timestamp(now());
+  let session = sessions.with_connection(|connection| {
+    db::expire_due_sessions(connection, &now)?;
+    db::find_session_by_resume_token(connection, token)
+  })?;
+  Ok(ResumedParkingSession {
+    id: session.id,
+    is_expired: session.expires_at.as_deref().is_some_and(|expires_at| expires_at <= now.as_str()),
+    status: session.status,
+    lat: session.parked_lat,
+    lng: session.parked_lng,
+    accuracy_meters: session.parked_accuracy_meters,
+    zone_number: session.confirmed_zone_number.or(session.captured_zone_number),
+    captured_rate: session.captured_rate,
+    duration_minutes: session.duration_minutes,
+    starts_at: session.starts_at,
+    expires_at: session.expires_at,
+    rules_rundown: None,
+  })
+}
+
+/// Local counterpart of `/api/parking/session/resume`.
+#[tauri::command]
+pub fn resume_parking_session(
+  sessions: tauri::State<'_, SessionStore>,
+  token: String,
+) -> CommandResult<ResumeParkingSessionResponse> {
+  Ok(ResumeParkingSessionResponse {
+    ok: true,
+    session: resume(&sessions, &token)?,
+  })
+}
+
+/// Starts the meter on a captured session once payment went through.
+#[tauri::command]
+pub fn activate_parking_session(
+  sessions: tauri::State<'_, SessionStore>,
+  session_id: String,
+  zone_number: String,
+  duration_minutes: u32,
+) -> CommandResult<ParkingSession> {
+  let zone_number = validate_activation(&zone_number, duration_minutes)?;
+
+  sessions.with_connection(|connection| {
+    let transaction = connection
+      .transaction()
+      .map_err(|error| CommandError::database("Failed to activate parking session", error))?;
+    let session = db::find_session(&transaction, &session_id)?;
+    if session.status != SessionStatus::Captured {
+      return Err(CommandError::Conflict(format!(
+        "Only captured parking sessions can be activated; this one is {}",
+        session.status.as_str()
+      )));
+    }
+
+    let (starts_at, expires_at, reminders) = activation_window(duration_minutes);
+    db::activate_session(
+      &transaction,
+      &session_id,
+      &db::Activation {
+        zone_number: &zone_number,
+        duration_minutes,
+        starts_at: &starts_at,
+        expires_at: &expires_at,
+      },
+      &starts_at,
+    )?;
+    db::queue_notifications(&transaction, &session_id, &reminders, &starts_at)?;
+
+    let activated = db::find_session(&transaction, &session_id)?;
+    transaction
+      .commit()
+      .map_err(|error| CommandError::database("Failed to activate parking session", error))?;
+    Ok(activated)
+  })
+  .inspect(|_| sessions.wake_reminders())
+}
+
+/// Pays for more time at the same spot: starts a new active session linked to `session_id` through
+/// `renewParentSessionId` and marks the old one renewed. The zone defaults to the one last paid for.
+#[tauri::command]
+pub fn renew_parking_session(
+  sessions: tauri::State<'_, SessionStore>,
+  session_id: String,
+  duration_minutes: u32,
+  zone_number: Option<String>,
+) -> CommandResult<ParkingSession> {
+  sessions.with_connection(|connection| {
+    let transaction = connection
+      .transaction()
+      .map_err(|error| CommandError::database("Failed to renew parking session", error))?;
+    db::expire_due_sessions(&transaction, &timestamp(now()))?;
+
+    let parent = db::find_session(&transaction, &session_id)?;
+    if !matches!(parent.status, SessionStatus::Active | SessionStatus::Expired) {
+      return Err(CommandError::Conflict(format!(
+        "Only active or expired parking sessions can be renewed; this one is {}",
+        parent.status.as_str()
+      )));
+    }
+
+    let zone_number = zone_number
+      .or_else(|| parent.confirmed_zone_number.clone())
+      .unwrap_or_default();
+    let zone_number = validate_activation(&zone_number, duration_minutes)?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let (starts_at, expires_at, reminders) = activation_window(duration_minutes);
+    db::insert_session(
+      &transaction,
+      &db::NewSession {
+        id: &id,
+        status: SessionStatus::Captured,
+        parked_lat: parent.parked_lat,
+        parked_lng: parent.parked_lng,
+        parked_accuracy_meters: parent.parked_accuracy_meters,
+        captured_zone_number: parent.captured_zone_number.as_deref(),
+        captured_rate: parent.captured_rate.as_deref(),
+        captured_category: &parent.captured_category,
+        resume_token: &new_resume_token(),
+        renew_parent_session_id: Some(&parent.id),
+        now: &starts_at,
+      },
+    )?;
+    db::activate_session(
+      &transaction,
+      &id,
+      &db::Activation {
+        zone_number: &zone_number,
+        duration_minutes,
+        starts_at: &starts_at,
+        expires_at: &expires_at,
+      },
+      &starts_at,
+    )?;
+    db::queue_notifications(&transaction, &id, &reminders, &starts_at)?;
+    db::set_status(&transaction, &parent.id, SessionStatus::Renewed, &starts_at)?;
+    db::skip_pending_notifications(
+      &transaction,
+      &parent.id,
+      Some(&[
+        reminders::NotificationType::RenewReminder.as_str(),
+        reminders::NotificationType::ParkingExpired.as_str(),
+      ]),
+      "Superseded by renewed session.",
+      &starts_at,
+    )?;
+
+    let renewed = db::find_session(&transaction, &id)?;
+    transaction
+      .commit()
+      .map_err(|error| CommandError::database("Failed to renew parking session", error))?;
+    Ok(renewed)
+  })
+  .inspect(|_| sessions.wake_reminders())
+}
+
+#[tauri::command]
+pub fn cancel_parking_session(
+  sessions: tauri::State<'_, SessionStore>,
+  session_id: String,
+) -> CommandResult<ParkingSession> {
+  sessions.with_connection(|connection| {
+    let transaction = connection
+      .transaction()
+      .map_err(|error| CommandError::database("Failed to cancel parking session", error))?;
+    let now = timestamp(now());
+    db::expire_due_sessions(&transaction, &now)?;
+
+    let session = db::find_session(&transaction, &session_id)?;
+    if !matches!(session.status, SessionStatus::Captured | SessionStatus::Active) {
+      return Err(CommandError::Conflict(format!(
+        "Only captured or active parking sessions can be cancelled; this one is {}",
+        session.status.as_str()
+      )));
+    }
+
+    db::set_status(&transaction, &session_id, SessionStatus::Cancelled, &now)?;
+    db::skip_pending_notifications(&transaction, &session_id, None, "Session was cancelled.", &now)?;
+    let cancelled = db::find_session(&transaction, &session_id)?;
+    transaction
+      .commit()
+      .map_err(|error| CommandError::database("Failed to cancel parking session", error))?;
+    Ok(cancelled)
+  })
+  .inspect(|_| sessions.wake_reminders())
+}
+
+/// Parking history, newest first.
+#[tauri::command]
+pub fn list_parking_sessions(
+  sessions: tauri::State<'_, SessionStore>,
+  limit: Option<u32>,
+) -> CommandResult<Vec<ParkingSession>> {
+  let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT).clamp(1, MAX_HISTORY_LIMIT);
+  sessions.with_connection(|connection| {
+    db::expire_due_sessions(connection, &timestamp(now()))?;
+    db::list_sessions(connection, limit)
+  })
+}
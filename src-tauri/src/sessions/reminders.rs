@@ -0,0 +1,256 @@
+//! Desktop stand-in for `/api/jobs/parking-agent-tick`: a background thread that delivers the
+//! reminders queued for active sessions as OS notifications instead of SMS.
+
+use super::db::{self, ParkingSession};
+use super::{now, timestamp, SessionStatus, SessionStore};
+use crate::error::{CommandError, CommandResult};
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use tauri::Manager;
+use tauri_plugin_notification::NotificationExt;
+
+/// Longest the thread sleeps between checks, so the tray countdown stays current and a changed
+/// system clock or a resumed laptop is noticed reasonably soon.
+const MAX_IDLE: Duration = Duration::from_secs(60);
+const RETRY_AFTER_ERROR: Duration = Duration::from_secs(30);
+
+/// Same stages as `PARKING_NOTIFICATION_TYPES` in `lib/parking-agent/types.ts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationType {
+  PaymentConfirmed,
+  PostPaymentInfo,
+  RenewReminder,
+  ParkingExpired,
+}
+
+impl NotificationType {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      NotificationType::PaymentConfirmed => "payment_confirmed",
+      NotificationType::PostPaymentInfo => "post_payment_info",
+      NotificationType::RenewReminder => "renew_reminder",
+      NotificationType::ParkingExpired => "parking_expired",
+    }
+  }
+
+  fn parse(value: &str) -> Option<Self> {
+    match value {
+      "payment_confirmed" => Some(NotificationType::PaymentConfirmed),
+      "post_payment_info" => Some(NotificationType::PostPaymentInfo),
+      "renew_reminder" => Some(NotificationType::RenewReminder),
+      "parking_expired" => Some(NotificationType::ParkingExpired),
+      _ => None,
+    }
+  }
+}
+
+/// When each reminder fires, using the timings of `/api/parking/payment/execute`.
+pub fn schedule(
+  starts_at: DateTime<Utc>,
+  expires_at: DateTime<Utc>,
+  duration_minutes: u32,
+) -> Vec<(&'static str, String)> {
+  let renew_reminder_at = match expires_at - chrono::Duration::minutes(10) {
+    reminder_at if duration_minutes >= 10 && reminder_at > starts_at => reminder_at,
+    _ => starts_at + chrono::Duration::seconds(60),
+  };
+
+  vec![
+    (NotificationType::PaymentConfirmed.as_str(), timestamp(starts_at)),
+    (
+      NotificationType::PostPaymentInfo.as_str(),
+      timestamp(starts_at + chrono::Duration::seconds(30)),
+    ),
+    (NotificationType::RenewReminder.as_str(), timestamp(renew_reminder_at)),
+    (NotificationType::ParkingExpired.as_str(), timestamp(expires_at)),
+  ]
+}
+
+/// Starts the reminder thread. Reminders live in the session database, so ones that came due while
+/// the app was closed are delivered (or skipped as stale) on the first pass.
+pub fn start(app: tauri::AppHandle) -> CommandResult<()> {
+  let (sender, receiver) = mpsc::channel();
+  app.state::<SessionStore>().set_waker(sender);
+
+  thread::Builder::new()
+    .name("parking-reminders".to_string())
+    .spawn(move || run(app, receiver))
+    .map_err(|error| CommandError::io("Failed to start parking reminder thread", error))?;
+  Ok(())
+}
+
+fn run(app: tauri::AppHandle, wake: Receiver<()>) {
+  loop {
+    let store = app.state::<SessionStore>();
+    let wait = match store.with_connection(|connection| deliver_due(&app, connection)) {
+      Ok(Some(next_at)) => (next_at - now()).to_std().unwrap_or(Duration::ZERO).min(MAX_IDLE),
+      Ok(None) => MAX_IDLE,
+      Err(error) => {
+        log::warn!("Failed to deliver parking reminders: {error}");
+        RETRY_AFTER_ERROR
+      }
+    };
+
+    if let Err(RecvTimeoutError::Disconnected) = wake.recv_timeout(wait) {
+      return;
+    }
+  }
+}
+
+/// Sends every reminder that is due, refreshes the tray countdown and returns when the next
+/// reminder is due.
+fn deliver_due(app: &tauri::AppHandle, connection: &Connection) -> CommandResult<Option<DateTime<Utc>>> {
+  let now = now();
+  let now_text = timestamp(now);
+
+  for due in db::due_notifications(connection, &now_text)? {
+    let Some(notification_type) = NotificationType::parse(&due.notification_type) else {
+      db::mark_notification_skipped(connection, due.id, "Unknown notification type.", &now_text)?;
+      continue;
+    };
+
+    if matches!(due.session.status, SessionStatus::Cancelled | SessionStatus::Renewed) {
+      db::mark_notification_skipped(connection, due.id, "Session is no longer active.", &now_text)?;
+      continue;
+    }
+    if notification_type != NotificationType::ParkingExpired && parse_timestamp(&due.session.expires_at) <= Some(now) {
+      db::mark_notification_skipped(connection, due.id, "Parking had already expired.", &now_text)?;
+      continue;
+    }
+
+    let (title, body) = message(notification_type, &due.session, now);
+    match app.notification().builder().title(title).body(&body).show() {
+      Ok(()) => db::mark_notification_sent(connection, due.id, &body, &now_text)?,
+      Err(error) => {
+        log::warn!("Failed to show {} notification: {error}", notification_type.as_str());
+        db::mark_notification_failed(connection, due.id, &error.to_string(), &now_text)?;
+      }
+    }
+  }
+
+  db::expire_due_sessions(connection, &now_text)?;
+  crate::tray::show_session(app, db::running_session(connection, &now_text)?.as_ref(), now);
+  Ok(parse_timestamp(&db::next_notification_at(connection)?))
+}
+
+fn parse_timestamp(value: &Option<String>) -> Option<DateTime<Utc>> {
+  let value = value.as_deref()?;
+  DateTime::parse_from_rfc3339(value)
+    .ok()
+    .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+fn local_time(value: &Option<String>) -> String {
+  parse_timestamp(value)
+    .map(|timestamp| {
+      timestamp
+        .with_timezone(&chrono::Local)
+        .format("%b %-d, %-I:%M %p")
+        .to_string()
+    })
+    .unwrap_or_else(|| "Unknown time".to_string())
+}
+
+/// Notification title and body; the wording follows the SMS fallbacks in `lib/parking-agent/llm.ts`.
+fn message(
+  notification_type: NotificationType,
+  session: &ParkingSession,
+  now: DateTime<Utc>,
+) -> (&'static str, String) {
+  let zone = session
+    .confirmed_zone_number
+    .as_deref()
+    .or(session.captured_zone_number.as_deref())
+    .unwrap_or("unknown");
+  let expires = local_time(&session.expires_at);
+
+  match notification_type {
+    NotificationType::PaymentConfirmed => (
+      "Parking paid",
+      format!("Payment confirmed for zone {zone}. Expires {expires}."),
+    ),
+    NotificationType::PostPaymentInfo => (
+      "Parking info",
+      format!("Check posted signs for exact limits at your stall. Renew in ParkOS before {expires}."),
+    ),
+    NotificationType::RenewReminder => {
+      let minutes_left = parse_timestamp(&session.expires_at)
+        .map(|expires_at| (expires_at - now).num_minutes().max(0))
+        .unwrap_or(0);
+      (
+        "Parking expires soon",
+        format!("{minutes_left} min left in zone {zone}. Open ParkOS to renew."),
+      )
+    }
+    NotificationType::ParkingExpired => (
+      "Parking expired",
+      format!("Parking in zone {zone} expired at {expires}. Open ParkOS to renew."),
+    ),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+
+  /// Seconds after the session start at which each reminder fires, in schedule order.
+  fn offsets(duration_minutes: u32) -> Vec<(&'static str, i64)> {
+    let starts_at = Utc.with_ymd_and_hms(2026, 3, 6, 17, 0, 0).unwrap();
+    let expires_at = starts_at + chrono::Duration::minutes(duration_minutes.into());
+    schedule(starts_at, expires_at, duration_minutes)
+      .into_iter()
+      .map(|(notification_type, at)| {
+        let at = DateTime::parse_from_rfc3339(&at).unwrap();
+        (notification_type, (at.with_timezone(&Utc) - starts_at).num_seconds())
+      })
+      .collect()
+  }
+
+  #[test]
+  fn reminder_times() {
+    let cases = [
+      // A normal stay: the renew reminder is 10 minutes before expiry.
+      (120, [0, 30, 110 * 60, 120 * 60]),
+      (11, [0, 30, 60, 11 * 60]),
+      // Too short for a 10-minute warning: remind a minute in instead of before the start.
+      (10, [0, 30, 60, 10 * 60]),
+      (5, [0, 30, 60, 5 * 60]),
+      (1, [0, 30, 60, 60]),
+    ];
+
+    let types = ["payment_confirmed", "post_payment_info", "renew_reminder", "parking_expired"];
+    for (duration_minutes, expected) in cases {
+      let expected: Vec<(&str, i64)> = types.into_iter().zip(expected).collect();
+      assert_eq!(offsets(duration_minutes), expected, "{duration_minutes} minutes");
+    }
+  }
+
+  #[test]
+  fn reminders_never_fire_before_the_start_or_after_expiry() {
+    for duration_minutes in 1..=180 {
+      for (notification_type, offset) in offsets(duration_minutes) {
+        assert!(
+          (0..=i64::from(duration_minutes) * 60).contains(&offset),
+          "{notification_type} at {offset}s of a {duration_minutes}-minute stay"
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn notification_types_round_trip() {
+    for notification_type in [
+      NotificationType::PaymentConfirmed,
+      NotificationType::PostPaymentInfo,
+      NotificationType::RenewReminder,
+      NotificationType::ParkingExpired,
+    ] {
+      assert_eq!(NotificationType::parse(notification_type.as_str()), Some(notification_type));
+    }
+    assert_eq!(NotificationType::parse("sms"), None);
+  }
+}
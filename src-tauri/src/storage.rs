@@ -0,0 +1,218 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Path of the last-known-good copy kept next to `path`, e.g. `payment_profile.enc.bak`.
+pub fn backup_path(path: &Path) -> PathBuf {
+  sibling_with_suffix(path, ".bak")
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+  sibling_with_suffix(path, ".tmp")
+}
+
+fn sibling_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+  let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+  file_name.push(suffix);
+  path.with_file_name(file_name)
+}
+
+/// Replaces `path` with `contents` so that a crash leaves either the old or the new file, never a
+/// truncated one. The previous contents are kept at `backup_path(path)`, written the same way.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+  if path.exists() {
+    let previous = fs::read(path)?;
+    replace_synced(&backup_path(path), &previous)?;
+  }
+
+  replace_synced(path, contents)
+}
+
+/// Puts the last-known-good copy back in place of a corrupt `path` without touching the backup.
+pub fn restore_backup(path: &Path) -> std::io::Result<()> {
+  let contents = fs::read(backup_path(path))?;
+  replace_synced(path, &contents)
+}
+
+/// Writes `contents` to a synced temp sibling and renames it over `path`.
+fn replace_synced(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+  let temp = temp_path(path);
+  let result = write_synced(&temp, contents)
+    .and_then(|_| fs::rename(&temp, path))
+    .and_then(|_| sync_parent_dir(path));
+
+  if result.is_err() {
+    let _ = fs::remove_file(&temp);
+  }
+
+  result
+}
+
+/// Overwrites the file with zeros before unlinking it so the old bytes are not left in free
+/// blocks. Best effort on copy-on-write and journaling filesystems. Missing files are ignored.
+pub fn shred_file(path: &Path) -> std::io::Result<()> {
+  let len = match fs::metadata(path) {
+    Ok(metadata) => metadata.len(),
+    Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+    Err(error) => return Err(error),
+  };
+
+  let mut file = fs::OpenOptions::new().write(true).open(path)?;
+  file.seek(SeekFrom::Start(0))?;
+  let zeros = [0u8; 4096];
+  let mut remaining = len;
+  while remaining > 0 {
+    let chunk = remaining.min(zeros.len() as u64) as usize;
+    file.write_all(&zeros[..chunk])?;
+    remaining -= chunk as u64;
+  }
+  file.sync_all()?;
+  drop(file);
+
+  fs::remove_file(path)
+}
+
+/// Shreds every file below `dir`, then removes the directory tree. Missing directories are ignored.
+pub fn shred_dir(dir: &Path) -> std::io::Result<()> {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+    Err(error) => return Err(error),
+  };
+
+  for entry in entries {
+    let entry = entry?;
+    let path = entry.path();
+    let file_type = entry.file_type()?;
+    if file_type.is_symlink() {
+      fs::remove_file(&path)?;
+    } else if file_type.is_dir() {
+      shred_dir(&path)?;
+    } else {
+      shred_file(&path)?;
+    }
+  }
+
+  fs::remove_dir(dir)
+}
+
+fn write_synced(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+  let mut file = fs::File::create(path)?;
+  file.write_all(contents)?;
+  file.sync_all()
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> std::io::Result<()> {
+  match path.parent() {
+    Some(parent) => fs::File::open(parent)?.sync_all(),
+    None => Ok(()),
+  }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> std::io::Result<()> {
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+      .collect();
+    names.sort();
+    names
+  }
+
+  #[test]
+  fn first_write_creates_the_file_without_a_backup() {
+    let dir = TempDir::new();
+    let path = dir.join("profile.enc");
+
+    write_atomic(&path, b"first").unwrap();
+
+    assert_eq!(fs::read(&path).unwrap(), b"first");
+    assert!(!backup_path(&path).exists());
+  }
+
+  #[test]
+  fn write_moves_the_previous_contents_into_the_backup() {
+    let dir = TempDir::new();
+    let path = dir.join("profile.enc");
+
+    write_atomic(&path, b"first").unwrap();
+    write_atomic(&path, b"second").unwrap();
+    write_atomic(&path, b"third").unwrap();
+
+    assert_eq!(fs::read(&path).unwrap(), b"third");
+    assert_eq!(fs::read(backup_path(&path)).unwrap(), b"second");
+  }
+
+  #[test]
+  fn write_leaves_no_temp_files_behind() {
+    let dir = TempDir::new();
+    let path = dir.join("profile.enc");
+
+    write_atomic(&path, b"first").unwrap();
+    write_atomic(&path, b"second").unwrap();
+
+    assert_eq!(file_names(dir.path()), ["profile.enc", "profile.enc.bak"]);
+  }
+
+  #[test]
+  fn restore_backup_replaces_a_truncated_file() {
+    let dir = TempDir::new();
+    let path = dir.join("profile.enc");
+    write_atomic(&path, b"good").unwrap();
+    write_atomic(&path, b"newer").unwrap();
+    fs::write(&path, b"ne").unwrap();
+
+    restore_backup(&path).unwrap();
+
+    assert_eq!(fs::read(&path).unwrap(), b"good");
+    assert_eq!(fs::read(backup_path(&path)).unwrap(), b"good");
+    assert_eq!(file_names(dir.path()), ["profile.enc", "profile.enc.bak"]);
+  }
+
+  #[test]
+  fn restore_backup_fails_without_a_backup() {
+    let dir = TempDir::new();
+    let path = dir.join("profile.enc");
+    fs::write(&path, b"ne").unwrap();
+
+    assert!(restore_backup(&path).is_err());
+    assert_eq!(fs::read(&path).unwrap(), b"ne");
+  }
+
+  #[test]
+  fn shred_file_removes_the_file_and_ignores_missing_ones() {
+    let dir = TempDir::new();
+    let path = dir.join("secret.json");
+    fs::write(&path, b"4111111111111111").unwrap();
+
+    shred_file(&path).unwrap();
+    shred_file(&path).unwrap();
+
+    assert!(!path.exists());
+  }
+
+  #[test]
+  fn shred_dir_removes_nested_files() {
+    let dir = TempDir::new();
+    let root = dir.join("data");
+    fs::create_dir_all(root.join("nested")).unwrap();
+    fs::write(root.join("a.txt"), b"a").unwrap();
+    fs::write(root.join("nested").join("b.txt"), b"b").unwrap();
+
+    shred_dir(&root).unwrap();
+    shred_dir(&root).unwrap();
+
+    assert!(!root.exists());
+  }
+}
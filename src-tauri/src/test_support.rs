@@ -0,0 +1,28 @@
+//! Helpers shared by the unit tests.
+
+use std::path::{Path, PathBuf};
+
+/// A fresh directory under the system temp dir, removed with everything in it on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+  pub fn new() -> Self {
+    let path = std::env::temp_dir().join(format!("parkos-test-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&path).expect("temp dir can be created");
+    TempDir(path)
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.0
+  }
+
+  pub fn join(&self, name: &str) -> PathBuf {
+    self.0.join(name)
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
@@ -0,0 +1,172 @@
+//! Tray icon that shows the running parking session and keeps the app reachable while the window
+//! is closed.
+
+use crate::sessions::ParkingSession;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{Emitter, Manager};
+
+const TRAY_ID: &str = "parking";
+pub const MAIN_WINDOW: &str = "main";
+/// Event the parking page listens for to handle a tray action in the window.
+const TRAY_ACTION_EVENT: &str = "tray-action";
+/// Page with the capture, renew and map flows. Other pages get sent here with the action in the query.
+const PARKING_PAGE: &str = "/parking";
+
+const PARKED_ID: &str = "parked";
+const RENEW_ID: &str = "renew";
+const SHOW_LOCATION_ID: &str = "show-parked-location";
+const QUIT_ID: &str = "quit";
+
+/// Menu items whose text or availability follows the running session.
+pub struct TrayMenu {
+  status: MenuItem<tauri::Wry>,
+  renew: MenuItem<tauri::Wry>,
+  show_location: MenuItem<tauri::Wry>,
+  session_id: Mutex<Option<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrayAction<'a> {
+  action: &'a str,
+  session_id: Option<String>,
+}
+
+pub fn create(app: &tauri::AppHandle) -> tauri::Result<()> {
+  let status = MenuItem::with_id(app, "status", "No active parking", false, None::<&str>)?;
+  let parked = MenuItem::with_id(app, PARKED_ID, "I have parked", true, None::<&str>)?;
+  let renew = MenuItem::with_id(app, RENEW_ID, "Renew", false, None::<&str>)?;
+  let show_location = MenuItem::with_id(app, SHOW_LOCATION_ID, "Show parked location", false, None::<&str>)?;
+  let quit = MenuItem::with_id(app, QUIT_ID, "Quit", true, None::<&str>)?;
+  let menu = Menu::with_items(
+    app,
+    &[
+      &status,
+      &PredefinedMenuItem::separator(app)?,
+      &parked,
+      &renew,
+      &show_location,
+      &PredefinedMenuItem::separator(app)?,
+      &quit,
+    ],
+  )?;
+
+  let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+    .tooltip("ParkOS")
+    .menu(&menu)
+    .on_menu_event(handle_menu_event);
+  if let Some(icon) = app.default_window_icon() {
+    tray = tray.icon(icon.clone());
+  }
+  tray.build(app)?;
+
+  app.manage(TrayMenu {
+    status,
+    renew,
+    show_location,
+    session_id: Mutex::new(None),
+  });
+  Ok(())
+}
+
+/// Updates the tooltip and menu for the session whose meter is running, if any.
+pub fn show_session(app: &tauri::AppHandle, session: Option<&ParkingSession>, now: DateTime<Utc>) {
+  let Some(menu) = app.try_state::<TrayMenu>() else {
+    return;
+  };
+
+  let status = session.map_or_else(|| "No active parking".to_string(), |session| describe(session, now));
+  if let Some(tray) = app.tray_by_id(TRAY_ID) {
+    if let Err(error) = tray.set_tooltip(Some(format!("ParkOS: {status}"))) {
+      log::warn!("Failed to update tray tooltip: {error}");
+    }
+  }
+
+  let running = session.is_some();
+  let updated = menu
+    .status
+    .set_text(&status)
+    .and_then(|_| menu.renew.set_enabled(running))
+    .and_then(|_| menu.show_location.set_enabled(running));
+  if let Err(error) = updated {
+    log::warn!("Failed to update tray menu: {error}");
+  }
+
+  if let Ok(mut session_id) = menu.session_id.lock() {
+    *session_id = session.map(|session| session.id.clone());
+  };
+}
+
+fn describe(session: &ParkingSession, now: DateTime<Utc>) -> String {
+  let zone = session
+    .confirmed_zone_number
+    .as_deref()
+    .or(session.captured_zone_number.as_deref())
+    .unwrap_or("unknown");
+  let minutes_left = session
+    .expires_at
+    .as_deref()
+    .and_then(|expires_at| DateTime::parse_from_rfc3339(expires_at).ok())
+    .map(|expires_at| (expires_at.with_timezone(&Utc) - now).num_minutes().max(0))
+    .unwrap_or(0);
+
+  match minutes_left {
+    minutes if minutes >= 60 => format!("Zone {zone}: {}h {:02}m left", minutes / 60, minutes % 60),
+    minutes => format!("Zone {zone}: {minutes} min left"),
+  }
+}
+
+fn handle_menu_event(app: &tauri::AppHandle, event: MenuEvent) {
+  let action = match event.id().as_ref() {
+    QUIT_ID => return app.exit(0),
+    PARKED_ID => PARKED_ID,
+    RENEW_ID => RENEW_ID,
+    SHOW_LOCATION_ID => SHOW_LOCATION_ID,
+    _ => return,
+  };
+
+  show_main_window(app);
+  let session_id = app
+    .try_state::<TrayMenu>()
+    .and_then(|menu| menu.session_id.lock().ok().and_then(|session_id| session_id.clone()));
+  if let Err(error) = send_action(app, TrayAction { action, session_id }) {
+    log::warn!("Failed to send tray action {action}: {error}");
+  }
+}
+
+/// Emits the action when the parking page is showing, or opens that page with `?trayAction=` and
+/// `&sessionId=` so it runs the action once loaded.
+fn send_action(app: &tauri::AppHandle, action: TrayAction) -> tauri::Result<()> {
+  let Some(window) = app.get_webview_window(MAIN_WINDOW) else {
+    return app.emit(TRAY_ACTION_EVENT, action);
+  };
+  let mut target = window.url()?;
+  let path = target.path().trim_end_matches('/').trim_end_matches(".html");
+  if path == PARKING_PAGE {
+    return app.emit(TRAY_ACTION_EVENT, action);
+  }
+
+  target.set_path(PARKING_PAGE);
+  target.set_fragment(None);
+  {
+    let mut query = target.query_pairs_mut();
+    query.clear().append_pair("trayAction", action.action);
+    if let Some(session_id) = &action.session_id {
+      query.append_pair("sessionId", session_id);
+    }
+  }
+  window.navigate(target)
+}
+
+pub fn show_main_window(app: &tauri::AppHandle) {
+  if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+    let shown = window.unminimize().and_then(|_| window.show()).and_then(|_| window.set_focus());
+    if let Err(error) = shown {
+      log::warn!("Failed to show main window: {error}");
+    }
+  }
+}
@@ -0,0 +1,295 @@
+//! Offline destination search over `data/slo-downtown-places.json`. Ranking follows
+//! `rankDestinationCandidates` in `lib/trip-agent/orchestrator.ts`, with tokens allowed to match
+//! on a typed prefix or a single typo. Places that match nothing are kept as low-scored fallback
+//! candidates, as they are there.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const PLACES_JSON: &str = include_str!("../../../data/slo-downtown-places.json");
+const ADDRESS_WEIGHT: f64 = 0.35;
+const LOCALITY: &str = "san luis obispo";
+const LOCALITY_BOOST: f64 = 0.2;
+/// Keeps gazetteer order as the tie-breaker, like the result index does for Google Places.
+const ORDER_PENALTY: f64 = 0.001;
+const MIN_PREFIX_CHARS: usize = 3;
+const MIN_TYPO_CHARS: usize = 4;
+const FALLBACK_REASON: &str = "low lexical match, kept as fallback candidate";
+
+/// Bundled data file name and contents, for the diagnostics bundle.
+pub const BUNDLED_DATASET: (&str, &str) = ("slo-downtown-places.json", PLACES_JSON);
+
+#[derive(Deserialize)]
+struct PlacesFile {
+  places: Vec<Place>,
+}
+
+#[derive(Deserialize)]
+struct Place {
+  id: String,
+  name: String,
+  #[serde(default)]
+  aliases: Vec<String>,
+  address: String,
+  lat: f64,
+  lng: f64,
+}
+
+/// Parsed once at startup and shared through Tauri state, like the zone index.
+pub struct Gazetteer {
+  places: Vec<Place>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DestinationCandidate {
+  pub destination: String,
+  pub street: String,
+  pub formatted_address: String,
+  pub latitude: f64,
+  pub longitude: f64,
+  /// Gazetteer id, in place of a Google place id.
+  pub place_id: Option<String>,
+  /// The alias that matched better than the name, if any.
+  pub matched_alias: Option<String>,
+  pub score: f64,
+  pub name_score: f64,
+  pub address_score: f64,
+  pub locality_boost: f64,
+  pub reasons: Vec<String>,
+}
+
+impl Gazetteer {
+  pub fn load_bundled() -> Result<Self, String> {
+    let file = serde_json::from_str::<PlacesFile>(PLACES_JSON)
+      .map_err(|error| format!("Failed to parse bundled places gazetteer: {error}"))?;
+
+    let mut ids = HashSet::new();
+    for place in &file.places {
+      if !ids.insert(place.id.as_str()) {
+        return Err(format!("Duplicate place id {:?} in bundled places gazetteer", place.id));
+      }
+      if place.name.trim().is_empty() || place.address.trim().is_empty() {
+        return Err(format!("Place {} in bundled places gazetteer needs a name and address", place.id));
+      }
+      if !(-90.0..=90.0).contains(&place.lat) || !(-180.0..=180.0).contains(&place.lng) {
+        return Err(format!("Place {} in bundled places gazetteer has invalid coordinates", place.id));
+      }
+    }
+
+    Ok(Gazetteer { places: file.places })
+  }
+
+  /// Places ranked against `query`, best first. Places sharing no token with it still fill the list,
+  /// below every match.
+  pub fn search(&self, query: &str, limit: usize) -> Vec<DestinationCandidate> {
+    let query_tokens = tokenize(query);
+    if query_tokens.is_empty() {
+      return Vec::new();
+    }
+
+    let mut candidates: Vec<DestinationCandidate> = self
+      .places
+      .iter()
+      .enumerate()
+      .map(|(index, place)| rank(place, index, &query_tokens))
+      .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates.truncate(limit);
+    candidates
+  }
+}
+
+/// Score lead of the best candidate over the runner-up; a small gap means the query is ambiguous.
+pub fn top_two_score_gap(candidates: &[DestinationCandidate]) -> Option<f64> {
+  match candidates {
+    [first, second, ..] => Some(first.score - second.score),
+    _ => None,
+  }
+}
+
+fn rank(place: &Place, index: usize, query_tokens: &HashSet<String>) -> DestinationCandidate {
+  let (name_matches, matched_alias) = std::iter::once((&place.name, None))
+    .chain(place.aliases.iter().map(|alias| (alias, Some(alias))))
+    .map(|(name, alias)| (count_matches(query_tokens, &tokenize(name)), alias))
+    // `max_by_key` keeps the last maximum; reverse so the name wins ties with its aliases.
+    .rev()
+    .max_by_key(|(matches, _)| *matches)
+    .unwrap_or((0, None));
+  let address_matches = count_matches(query_tokens, &tokenize(&place.address));
+
+  let name_score = name_matches as f64 / query_tokens.len() as f64;
+  let address_score = address_matches as f64 / query_tokens.len() as f64;
+  let locality_boost = if place.address.to_lowercase().contains(LOCALITY) { LOCALITY_BOOST } else { 0.0 };
+  let mut reasons = Vec::new();
+  if name_score == 0.0 && address_score == 0.0 {
+    reasons.push(FALLBACK_REASON.to_string());
+  }
+  if name_score > 0.0 {
+    reasons.push(format!("name match {:.0}%", name_score * 100.0));
+  }
+  if let Some(alias) = matched_alias.filter(|_| name_score > 0.0) {
+    reasons.push(format!("matched alias \"{alias}\""));
+  }
+  if address_score > 0.0 {
+    reasons.push(format!("address match {:.0}%", address_score * 100.0));
+  }
+  if locality_boost > 0.0 {
+    reasons.push("downtown SLO locality boost".to_string());
+  }
+
+  DestinationCandidate {
+    destination: place.name.clone(),
+    street: place.address.split(',').next().unwrap_or_default().trim().to_string(),
+    formatted_address: place.address.clone(),
+    latitude: place.lat,
+    longitude: place.lng,
+    place_id: Some(place.id.clone()),
+    matched_alias: matched_alias.filter(|_| name_score > 0.0).cloned(),
+    score: name_score + address_score * ADDRESS_WEIGHT + locality_boost - index as f64 * ORDER_PENALTY,
+    name_score,
+    address_score,
+    locality_boost,
+    reasons,
+  }
+}
+
+/// Lowercase alphanumeric words longer than one character, like `tokenize` in the orchestrator.
+fn tokenize(value: &str) -> HashSet<String> {
+  value
+    .to_lowercase()
+    .split(|c: char| !c.is_ascii_alphanumeric())
+    .filter(|token| token.len() > 1)
+    .map(str::to_string)
+    .collect()
+}
+
+/// How many query tokens match some candidate token.
+fn count_matches(query_tokens: &HashSet<String>, candidate_tokens: &HashSet<String>) -> usize {
+  query_tokens
+    .iter()
+    .filter(|query| candidate_tokens.iter().any(|candidate| tokens_match(query, candidate)))
+    .count()
+}
+
+/// Exact, a typed prefix ("fremo" for "fremont"), or one edit apart ("lunna" for "luna"). Street
+/// numbers only match exactly.
+fn tokens_match(query: &str, candidate: &str) -> bool {
+  if query == candidate {
+    return true;
+  }
+  if query.bytes().all(|byte| byte.is_ascii_digit()) {
+    return false;
+  }
+  (query.len() >= MIN_PREFIX_CHARS && candidate.starts_with(query))
+    || (query.len() >= MIN_TYPO_CHARS && candidate.len() >= MIN_TYPO_CHARS && one_edit_apart(query, candidate))
+}
+
+fn one_edit_apart(a: &str, b: &str) -> bool {
+  let (a, b) = (a.as_bytes(), b.as_bytes());
+  let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+  if longer.len() - shorter.len() > 1 {
+    return false;
+  }
+  let prefix = shorter.iter().zip(longer).take_while(|(x, y)| x == y).count();
+  if prefix == longer.len() {
+    return true;
+  }
+  if shorter.len() == longer.len() {
+    // One substitution, or two neighbouring letters swapped.
+    shorter[prefix + 1..] == longer[prefix + 1..]
+      || (prefix + 1 < shorter.len()
+        && shorter[prefix] == longer[prefix + 1]
+        && shorter[prefix + 1] == longer[prefix]
+        && shorter[prefix + 2..] == longer[prefix + 2..])
+  } else {
+    shorter[prefix..] == longer[prefix + 1..]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn gazetteer() -> Gazetteer {
+    let place = |id: &str, name: &str, aliases: &[&str], address: &str| Place {
+      id: id.to_string(),
+      name: name.to_string(),
+      aliases: aliases.iter().map(|alias| alias.to_string()).collect(),
+      address: address.to_string(),
+      lat: 35.28,
+      lng: -120.66,
+    };
+    Gazetteer {
+      places: vec![
+        place("luna-red", "Luna Red", &["Luna"], "1023 Chorro St, San Luis Obispo, CA 93401"),
+        place(
+          "mission",
+          "Mission San Luis Obispo",
+          &["Old Mission", "Mission"],
+          "751 Palm St, San Luis Obispo, CA 93401",
+        ),
+        place("fremont", "Fremont Theater", &[], "1035 Monterey St, San Luis Obispo, CA 93401"),
+        place("novo", "Novo Restaurant", &[], "726 Higuera St, San Luis Obispo, CA 93401"),
+      ],
+    }
+  }
+
+  fn best(query: &str) -> DestinationCandidate {
+    gazetteer().search(query, 5).remove(0)
+  }
+
+  #[test]
+  fn prefix_and_typo_matches() {
+    let cases = [("fremo", "Fremont Theater"), ("lunna red", "Luna Red"), ("resturant", "Novo Restaurant")];
+    for (query, expected) in cases {
+      let candidate = best(query);
+      assert_eq!(candidate.destination, expected, "{query}");
+      assert!(candidate.name_score > 0.0, "{query}");
+    }
+  }
+
+  #[test]
+  fn street_numbers_match_exactly() {
+    let candidate = best("1035");
+    assert_eq!(candidate.destination, "Fremont Theater");
+    assert!(candidate.address_score > 0.0);
+    assert_eq!(best("103").address_score, 0.0);
+    assert_eq!(best("1034").address_score, 0.0);
+  }
+
+  #[test]
+  fn name_wins_ties_with_its_aliases() {
+    let candidate = best("mission");
+    assert_eq!(candidate.destination, "Mission San Luis Obispo");
+    assert_eq!(candidate.matched_alias, None);
+    assert!(!candidate.reasons.iter().any(|reason| reason.starts_with("matched alias")));
+
+    let candidate = best("old mission");
+    assert_eq!(candidate.matched_alias.as_deref(), Some("Old Mission"));
+  }
+
+  #[test]
+  fn unmatched_places_are_kept_as_fallbacks() {
+    let candidates = gazetteer().search("zzzz qqqq", 5);
+    assert_eq!(candidates.len(), 4);
+    assert!(candidates.iter().all(|candidate| candidate.reasons[0] == FALLBACK_REASON));
+    assert!(candidates.iter().enumerate().all(|(index, candidate)| {
+      (candidate.score - (LOCALITY_BOOST - index as f64 * ORDER_PENALTY)).abs() < 1e-9
+    }));
+
+    let candidates = gazetteer().search("novo", 5);
+    assert_eq!(candidates[0].destination, "Novo Restaurant");
+    assert!(candidates[1..].iter().all(|candidate| candidate.reasons[0] == FALLBACK_REASON));
+  }
+
+  #[test]
+  fn score_gap_of_the_top_two() {
+    let candidates = gazetteer().search("luna red", 5);
+    let gap = top_two_score_gap(&candidates).unwrap();
+    assert!((gap - (candidates[0].score - candidates[1].score)).abs() < 1e-9);
+    assert!(gap > 0.9);
+    assert_eq!(top_two_score_gap(&candidates[..1]), None);
+    assert_eq!(top_two_score_gap(&[]), None);
+  }
+}
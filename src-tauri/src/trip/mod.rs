@@ -0,0 +1,128 @@
+//! Trip planning from a free-text prompt, offline. Mirrors the `ParsedTripIntent` shape of
+//! `lib/trip-parser.ts` and the destination ranking of the trip agent, so the desktop app can plan
+//! trips without an OpenAI or Google Places key.
+
+mod gazetteer;
+mod parser;
+
+pub use gazetteer::{DestinationCandidate, Gazetteer, BUNDLED_DATASET};
+use gazetteer::top_two_score_gap;
+
+use crate::calendar::TIME_ZONE;
+use crate::error::{CommandError, CommandResult};
+use crate::payment_profile::FieldError;
+use crate::rules;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+const MAX_PROMPT_CHARS: usize = 500;
+const DEFAULT_DESTINATION_LIMIT: u32 = 5;
+const MAX_DESTINATION_LIMIT: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TripParseConfidence {
+  /// Destination and arrival time (if any) were stated outright.
+  High,
+  /// The parser filled something in, like am/pm or the day, and says so in `warnings`.
+  Medium,
+  /// No destination, or conflicting times.
+  Low,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedTripIntent {
+  pub destination: String,
+  pub arrival_time_iso: Option<String>,
+  /// Like "Sat, Feb 22, 2026, 8:00 PM PST".
+  pub arrival_time_label: Option<String>,
+  pub timezone: &'static str,
+  pub confidence: TripParseConfidence,
+  pub warnings: Vec<String>,
+}
+
+/// Splits `prompt` into a destination and an arrival time in Los Angeles time. Relative phrases
+/// like "tomorrow" or "in 45 min" are read against `now` (an RFC 3339 timestamp), or the current
+/// time when it is missing.
+#[tauri::command]
+pub fn parse_trip_prompt(prompt: String, now: Option<String>) -> CommandResult<ParsedTripIntent> {
+  let prompt = prompt.trim();
+  let mut fields = Vec::new();
+  if prompt.is_empty() {
+    fields.push(FieldError {
+      field: "prompt".to_string(),
+      message: "Trip prompt is required.".to_string(),
+    });
+  } else if prompt.chars().count() > MAX_PROMPT_CHARS {
+    fields.push(FieldError {
+      field: "prompt".to_string(),
+      message: format!("Trip prompt must be at most {MAX_PROMPT_CHARS} characters."),
+    });
+  }
+  let now = match now.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+    Some(now) => DateTime::parse_from_rfc3339(now).ok().map(|now| now.with_timezone(&TIME_ZONE)),
+    None => Some(Utc::now().with_timezone(&TIME_ZONE)),
+  };
+  if now.is_none() {
+    fields.push(FieldError {
+      field: "now".to_string(),
+      message: "Reference time must be a timestamp like 2026-02-22T20:00:00-08:00.".to_string(),
+    });
+  }
+
+  match now {
+    Some(now) if fields.is_empty() => Ok(parse(prompt, now)),
+    _ => Err(CommandError::Validation { fields }),
+  }
+}
+
+pub fn parse(prompt: &str, now: DateTime<Tz>) -> ParsedTripIntent {
+  let intent = parser::parse(prompt, now);
+  ParsedTripIntent {
+    destination: intent.destination,
+    arrival_time_iso: intent.arrival.map(rules::timestamp),
+    arrival_time_label: intent
+      .arrival
+      .map(|arrival| arrival.format("%a, %b %-d, %Y, %-I:%M %p %Z").to_string()),
+    timezone: TIME_ZONE.name(),
+    confidence: intent.confidence,
+    warnings: intent.warnings,
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DestinationSearch {
+  pub query: String,
+  pub candidates: Vec<DestinationCandidate>,
+  /// Score lead of the best candidate over the runner-up; a small gap means the query is ambiguous.
+  pub top_two_score_gap: Option<f64>,
+}
+
+/// Ranks bundled downtown places against `query`, fully offline.
+#[tauri::command]
+pub fn search_destinations(
+  gazetteer: tauri::State<'_, Gazetteer>,
+  query: String,
+  limit: Option<u32>,
+) -> CommandResult<DestinationSearch> {
+  let query = query.trim();
+  if query.is_empty() || query.chars().count() > MAX_PROMPT_CHARS {
+    return Err(CommandError::Validation {
+      fields: vec![FieldError {
+        field: "query".to_string(),
+        message: format!("Destination must be between 1 and {MAX_PROMPT_CHARS} characters."),
+      }],
+    });
+  }
+
+  let limit = limit.unwrap_or(DEFAULT_DESTINATION_LIMIT).clamp(1, MAX_DESTINATION_LIMIT) as usize;
+  let candidates = gazetteer.search(query, limit);
+  Ok(DestinationSearch {
+    query: query.to_string(),
+    top_two_score_gap: top_two_score_gap(&candidates),
+    candidates,
+  })
+}
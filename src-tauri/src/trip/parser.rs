@@ -0,0 +1,481 @@
+//! Rule-based stand-in for `parseTripPromptWithLlm` in `lib/trip-parser.ts`: finds the arrival time
+//! in prompts like "Going to Luna Red today @ 8pm", "tomorrow at noon", "in 45 min" or "Fri 7:30"
+//! and keeps what is left as the destination. The same prompt and reference time always give the
+//! same result.
+
+use super::TripParseConfidence;
+use crate::calendar;
+use crate::rules;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Timelike, Weekday};
+use chrono_tz::Tz;
+use regex::{Captures, Regex};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+const MINUTES_PER_HALF_DAY: u32 = 12 * 60;
+const MINUTES_PER_DAY: u32 = 24 * 60;
+/// Relative arrivals further out than this are treated as a typo rather than a plan.
+const MAX_RELATIVE_MINUTES: f64 = 7.0 * MINUTES_PER_DAY as f64;
+/// Hours without am/pm in this range are read as morning, the rest as afternoon or evening.
+const MORNING_HOURS: std::ops::RangeInclusive<u32> = 7..=11;
+
+pub struct TripIntent {
+  /// Empty when the prompt only held a time.
+  pub destination: String,
+  pub arrival: Option<DateTime<Tz>>,
+  pub confidence: TripParseConfidence,
+  pub warnings: Vec<String>,
+}
+
+#[derive(Clone, Copy)]
+enum Day {
+  Today,
+  Tomorrow,
+  /// `next` skips today when it is that weekday already.
+  Weekday { weekday: Weekday, next: bool },
+}
+
+#[derive(Clone, Copy)]
+enum PartOfDay {
+  Morning,
+  Afternoon,
+  Evening,
+}
+
+impl PartOfDay {
+  fn default_minute(self) -> u32 {
+    match self {
+      PartOfDay::Morning => 9 * 60,
+      PartOfDay::Afternoon => 14 * 60,
+      PartOfDay::Evening => 19 * 60,
+    }
+  }
+
+  /// Minute of the day for an hour given without am/pm, e.g. 8 in the evening is 8 PM.
+  fn resolve(self, hour: u32, minute: u32) -> u32 {
+    match self {
+      PartOfDay::Morning => hour % 12 * 60 + minute,
+      PartOfDay::Evening if hour == 12 => MINUTES_PER_DAY + minute,
+      PartOfDay::Afternoon | PartOfDay::Evening => (hour % 12 + 12) * 60 + minute,
+    }
+  }
+}
+
+#[derive(Clone, Copy)]
+enum Clock {
+  /// Minute of the day; `MINUTES_PER_DAY` is midnight at the end of the day.
+  Exact(u32),
+  /// 1 to 12 without am/pm.
+  Ambiguous { hour: u32, minute: u32 },
+}
+
+struct Patterns {
+  relative: [Regex; 2],
+  now: Regex,
+  named_clock: Regex,
+  clock: Regex,
+  /// Full weekday names, then abbreviations, which are only tried when there is no full name so
+  /// "Sun Dance Cafe on Sunday" keeps its name.
+  day: [Regex; 2],
+  part_of_day: Regex,
+  lead: Regex,
+  trail: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+  static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+  PATTERNS.get_or_init(|| {
+    let compile = |pattern: &str| Regex::new(pattern).expect("trip pattern is valid");
+    let amount = r"(\d+(?:\.\d+)?|an?|one|half\s+an?)";
+    let unit = r"(minutes?|mins?|m|hours?|hrs?|h)";
+    let prefix = r"(?:(?:@|\bat|\bby|\baround|\babout|\bfor)\s*)?";
+    let day = |weekday: &str| {
+      compile(&format!(r"(?i)\b(?:(?:on|this|(next))\s+)?(?:(today)|(tomorrow|tmrw|tmr)|({weekday}))\b\.?"))
+    };
+    Patterns {
+      relative: [
+        compile(&format!(r"(?i)\bin\s+{amount}\s*{unit}\b")),
+        compile(&format!(r"(?i)\b{amount}\s*{unit}\s+from\s+now\b")),
+      ],
+      now: compile(r"(?i)\b(?:right\s+now|now|asap)\b"),
+      named_clock: compile(&format!(r"(?i){prefix}\b(noon|midday|midnight)\b")),
+      clock: compile(&format!(r"(?i)({prefix})\b(\d{{1,2}})(?::([0-5]\d))?\s*(a\.m|p\.m|am|pm|a|p)?\b")),
+      day: [
+        day("monday|tuesday|wednesday|thursday|friday|saturday|sunday"),
+        day("mon|tues|tue|weds|wed|thurs|thur|thu|fri|sat|sun"),
+      ],
+      part_of_day: compile(r"(?i)\b(?:(?:this|in\s+the)\s+)?(morning|afternoon|evening|night|tonight)\b"),
+      lead: compile(concat!(
+        r"(?i)^(?:(?:i'?m|i\s+am|we'?re|we\s+are|i'?ll\s+be|we'?ll\s+be|let'?s)\s+)?",
+        r"(?:visit(?:ing)?\s+|(?:(?:go|going|headed|heading|driving|walking|getting|get|trip|parking|park|",
+        r"arriving|arrive|meeting|meet|dinner|lunch|brunch|breakfast|coffee|drinks)\s+)?(?:to|at|near|for)\s+)",
+      )),
+      trail: compile(r"(?i)(?:[\s,.;:!?@-]+|\b(?:at|by|around|about|on|for|this|next|and|arriving|from|until)\b)+$"),
+    }
+  })
+}
+
+/// Claims the parts of the prompt that were read as time, so the rest can become the destination.
+struct Scan<'a> {
+  prompt: &'a str,
+  claimed: Vec<Range<usize>>,
+}
+
+impl<'a> Scan<'a> {
+  /// Every match of `pattern` outside already claimed text that `parse` accepts, in prompt order.
+  fn take<T>(&mut self, pattern: &Regex, parse: impl Fn(&Captures<'a>) -> Option<T>) -> Vec<(&'a str, T)> {
+    let prompt = self.prompt;
+    let mut found = Vec::new();
+    for captures in pattern.captures_iter(prompt) {
+      let whole = captures.get(0).expect("group 0 always matches");
+      let range = whole.range();
+      if self.claimed.iter().any(|claimed| claimed.start < range.end && range.start < claimed.end) {
+        continue;
+      }
+      if let Some(value) = parse(&captures) {
+        self.claimed.push(range);
+        found.push((whole.as_str().trim(), value));
+      }
+    }
+    found
+  }
+
+  fn remainder(&self) -> String {
+    let mut claimed = self.claimed.clone();
+    claimed.sort_by_key(|range| range.start);
+    let mut rest = String::new();
+    let mut cursor = 0;
+    for range in claimed {
+      rest.push_str(&self.prompt[cursor..range.start]);
+      rest.push(' ');
+      cursor = range.end;
+    }
+    rest.push_str(&self.prompt[cursor..]);
+    rest
+  }
+}
+
+pub fn parse(prompt: &str, now: DateTime<Tz>) -> TripIntent {
+  let patterns = patterns();
+  let now = now.with_second(0).and_then(|time| time.with_nanosecond(0)).unwrap_or(now);
+  let mut scan = Scan { prompt, claimed: Vec::new() };
+
+  let mut relative = Vec::new();
+  for pattern in &patterns.relative {
+    relative.extend(scan.take(pattern, parse_relative));
+  }
+  let asap = scan.take(&patterns.now, |_| Some(()));
+  let mut clocks = scan.take(&patterns.named_clock, parse_named_clock);
+  clocks.extend(scan.take(&patterns.clock, parse_clock));
+  let mut days = scan.take(&patterns.day[0], parse_day);
+  if days.is_empty() {
+    days = scan.take(&patterns.day[1], parse_day);
+  }
+  let parts = scan.take(&patterns.part_of_day, parse_part_of_day);
+
+  let mut warnings = Vec::new();
+  let mut assumed = false;
+  let mut conflict = false;
+
+  let times = relative.len() + asap.len() + clocks.len();
+  if times > 1 || days.len() > 1 || parts.len() > 1 || (relative.len() + asap.len() > 0 && times + days.len() > 1) {
+    conflict = true;
+    let used = relative
+      .first()
+      .map(|(text, _)| *text)
+      .or_else(|| asap.first().map(|(text, _)| *text))
+      .or_else(|| clocks.first().map(|(text, _)| *text))
+      .or_else(|| days.first().map(|(text, _)| *text))
+      .unwrap_or_default();
+    warnings.push(format!("The prompt mentions more than one arrival time; used \"{used}\"."));
+  }
+
+  let arrival = if let Some((_, minutes)) = relative.first() {
+    Some(now + Duration::minutes(i64::from(*minutes)))
+  } else if !asap.is_empty() {
+    Some(now)
+  } else {
+    let day = days.first().map(|(text, day)| (*text, *day));
+    let part = parts.first().map(|(text, part)| (*text, *part));
+    let clock = clocks.first().map(|(_, clock)| *clock);
+    resolve(now, day, part, clock, &mut warnings, &mut assumed)
+  };
+
+  let destination = destination(&scan.remainder());
+  if destination.is_empty() {
+    warnings.push("No destination found in the prompt; say where you are going.".to_string());
+  }
+
+  let confidence = if destination.is_empty() || conflict {
+    TripParseConfidence::Low
+  } else if assumed {
+    TripParseConfidence::Medium
+  } else {
+    TripParseConfidence::High
+  };
+
+  TripIntent {
+    destination,
+    arrival,
+    confidence,
+    warnings,
+  }
+}
+
+/// Absolute arrival from a day, part of day and clock time, any of which may be missing.
+fn resolve(
+  now: DateTime<Tz>,
+  day: Option<(&str, Day)>,
+  part: Option<(&str, (PartOfDay, bool))>,
+  clock: Option<Clock>,
+  warnings: &mut Vec<String>,
+  assumed: &mut bool,
+) -> Option<DateTime<Tz>> {
+  let today = now.date_naive();
+  // "tonight" pins the day as firmly as "today" does.
+  let tonight = matches!(part, Some((_, (_, true))));
+  let date = match day.map(|(_, day)| day) {
+    Some(Day::Today) => Some(today),
+    Some(Day::Tomorrow) => Some(today + Duration::days(1)),
+    Some(Day::Weekday { weekday, next }) => Some(next_weekday(today, weekday, next)),
+    None if tonight => Some(today),
+    None => None,
+  };
+
+  let minute = match (clock, part) {
+    (Some(Clock::Exact(minute)), _) => minute,
+    (Some(Clock::Ambiguous { hour, minute }), Some((_, (part, _)))) => part.resolve(hour, minute),
+    (Some(Clock::Ambiguous { hour, minute }), None) => {
+      let morning = hour % 12 * 60 + minute;
+      let evening = morning + MINUTES_PER_HALF_DAY;
+      let (preferred, other) = if MORNING_HOURS.contains(&hour) { (morning, evening) } else { (evening, morning) };
+      // On today's date, prefer whichever reading is still ahead.
+      let on = date.unwrap_or(today);
+      let chosen = if on == today && local_time(on, preferred) < now && local_time(on, other) >= now {
+        other
+      } else {
+        preferred
+      };
+      *assumed = true;
+      warnings.push(format!(
+        "Read {} as {}; add am or pm if you meant otherwise.",
+        format_clock_input(hour, minute),
+        local_time(on, chosen).format("%-I:%M %p")
+      ));
+      chosen
+    }
+    (None, Some((text, (part, _)))) => {
+      let minute = part.default_minute();
+      *assumed = true;
+      warnings.push(format!(
+        "Assumed {} for \"{text}\"; add a time to be exact.",
+        local_time(today, minute).format("%-I:%M %p")
+      ));
+      minute
+    }
+    (None, None) => {
+      if let Some((text, _)) = day {
+        *assumed = true;
+        warnings.push(format!("No arrival time given for \"{text}\"; add one like \"at 7pm\"."));
+      }
+      return None;
+    }
+  };
+
+  let arrival = match (date, day.map(|(_, day)| day)) {
+    (Some(date), Some(Day::Weekday { .. })) if local_time(date, minute) < now => {
+      local_time(date + Duration::days(7), minute)
+    }
+    (Some(date), _) => {
+      let arrival = local_time(date, minute);
+      if arrival < now {
+        *assumed = true;
+        warnings.push(format!("{} has already passed.", rules::format_local(arrival)));
+      }
+      arrival
+    }
+    (None, _) => {
+      let arrival = local_time(today, minute);
+      if arrival < now {
+        let tomorrow = local_time(today + Duration::days(1), minute);
+        *assumed = true;
+        warnings.push(format!(
+          "{} has already passed today, so this assumes {}.",
+          arrival.format("%-I:%M %p"),
+          rules::format_local(tomorrow)
+        ));
+        tomorrow
+      } else {
+        arrival
+      }
+    }
+  };
+  Some(arrival)
+}
+
+/// What is left once times and filler like "going to" are removed, with the prompt's own casing.
+fn destination(remainder: &str) -> String {
+  let patterns = patterns();
+  let mut destination = remainder.split_whitespace().collect::<Vec<_>>().join(" ");
+  loop {
+    let trimmed = destination.trim_start_matches(|c: char| c.is_whitespace() || ",.;:!?@-".contains(c));
+    let trimmed = patterns.lead.replace(trimmed, "");
+    let trimmed = patterns.trail.replace(&trimmed, "").trim().to_string();
+    if trimmed == destination {
+      return destination;
+    }
+    destination = trimmed;
+  }
+}
+
+fn parse_relative(captures: &Captures) -> Option<u32> {
+  let amount = captures[1].to_ascii_lowercase();
+  let amount = match amount.as_str() {
+    "a" | "an" | "one" => 1.0,
+    amount if amount.starts_with("half") => 0.5,
+    amount => amount.parse::<f64>().ok()?,
+  };
+  let per_unit = if captures[2].to_ascii_lowercase().starts_with('h') { 60.0 } else { 1.0 };
+  let minutes = (amount * per_unit).round();
+  (minutes <= MAX_RELATIVE_MINUTES).then_some(minutes as u32)
+}
+
+fn parse_named_clock(captures: &Captures) -> Option<Clock> {
+  Some(match captures[1].to_ascii_lowercase().as_str() {
+    "midnight" => Clock::Exact(MINUTES_PER_DAY),
+    _ => Clock::Exact(MINUTES_PER_HALF_DAY),
+  })
+}
+
+/// A bare number only counts as a time with a colon, am/pm, or a lead-in like "at" or "@", so
+/// street numbers stay in the destination.
+fn parse_clock(captures: &Captures) -> Option<Clock> {
+  let has_prefix = !captures[1].trim().is_empty();
+  let hour: u32 = captures[2].parse().ok()?;
+  let minute: Option<u32> = captures.get(3).map(|minute| minute.as_str().parse()).transpose().ok()?;
+  let pm = captures.get(4).map(|meridiem| meridiem.as_str().to_ascii_lowercase().starts_with('p'));
+  match pm {
+    Some(pm) if (1..=12).contains(&hour) => {
+      Some(Clock::Exact((hour % 12 + if pm { 12 } else { 0 }) * 60 + minute.unwrap_or(0)))
+    }
+    Some(_) => None,
+    None if !has_prefix && minute.is_none() => None,
+    None if hour == 0 || (13..=23).contains(&hour) => Some(Clock::Exact(hour * 60 + minute.unwrap_or(0))),
+    None if hour <= 12 => Some(Clock::Ambiguous {
+      hour,
+      minute: minute.unwrap_or(0),
+    }),
+    None => None,
+  }
+}
+
+fn parse_day(captures: &Captures) -> Option<Day> {
+  if captures.get(2).is_some() {
+    return Some(Day::Today);
+  }
+  if captures.get(3).is_some() {
+    return Some(Day::Tomorrow);
+  }
+  let name = captures.get(4)?.as_str().to_ascii_lowercase();
+  let weekday = [
+    ("mon", Weekday::Mon),
+    ("tue", Weekday::Tue),
+    ("wed", Weekday::Wed),
+    ("thu", Weekday::Thu),
+    ("fri", Weekday::Fri),
+    ("sat", Weekday::Sat),
+    ("sun", Weekday::Sun),
+  ]
+  .into_iter()
+  .find(|(prefix, _)| name.starts_with(prefix))?
+  .1;
+  Some(Day::Weekday {
+    weekday,
+    next: captures.get(1).is_some(),
+  })
+}
+
+/// The part of day, and whether it was "tonight".
+fn parse_part_of_day(captures: &Captures) -> Option<(PartOfDay, bool)> {
+  let name = captures[1].to_ascii_lowercase();
+  Some(match name.as_str() {
+    "morning" => (PartOfDay::Morning, false),
+    "afternoon" => (PartOfDay::Afternoon, false),
+    "tonight" => (PartOfDay::Evening, true),
+    _ => (PartOfDay::Evening, false),
+  })
+}
+
+fn next_weekday(today: NaiveDate, weekday: Weekday, next: bool) -> NaiveDate {
+  let ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+  let ahead = if ahead == 0 && next { 7 } else { ahead };
+  today + Duration::days(i64::from(ahead))
+}
+
+fn local_time(date: NaiveDate, minute: u32) -> DateTime<Tz> {
+  calendar::local_datetime(date.and_time(NaiveTime::MIN) + Duration::minutes(i64::from(minute)))
+}
+
+fn format_clock_input(hour: u32, minute: u32) -> String {
+  if minute == 0 {
+    format!("\"{hour}\"")
+  } else {
+    format!("\"{hour}:{minute:02}\"")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::NaiveDateTime;
+
+  /// Friday, February 20, 2026, 2:10 PM in Los Angeles.
+  fn now() -> DateTime<Tz> {
+    at("2026-02-20 14:10")
+  }
+
+  fn at(value: &str) -> DateTime<Tz> {
+    calendar::local_datetime(NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap())
+  }
+
+  #[test]
+  fn prompts() {
+    use TripParseConfidence::{High, Low, Medium};
+
+    // (prompt, destination, arrival, confidence, warning that must be present)
+    let cases = [
+      ("Going to Luna Red today @ 8pm", "Luna Red", Some("2026-02-20 20:00"), High, None),
+      ("Luna Red tomorrow at noon", "Luna Red", Some("2026-02-21 12:00"), High, None),
+      ("coffee at Kreuzberg in 45 min", "Kreuzberg", Some("2026-02-20 14:55"), High, None),
+      ("Novo Fri 7:30", "Novo", Some("2026-02-20 19:30"), Medium, Some("Read \"7:30\" as 7:30 PM")),
+      ("Madonna Inn tonight", "Madonna Inn", Some("2026-02-20 19:00"), Medium, Some("Assumed 7:00 PM")),
+      ("Sun Dance Cafe sunday at 10am", "Sun Dance Cafe", Some("2026-02-22 10:00"), High, None),
+      ("Mission Plaza", "Mission Plaza", None, High, None),
+      ("tomorrow at noon", "", Some("2026-02-21 12:00"), Low, Some("No destination found")),
+      ("Madonna Inn at 5 and at 7pm", "Madonna Inn", Some("2026-02-20 17:00"), Low, Some("more than one arrival time")),
+    ];
+
+    for (prompt, destination, arrival, confidence, warning) in cases {
+      let intent = parse(prompt, now());
+      assert_eq!(intent.destination, destination, "{prompt}");
+      assert_eq!(intent.arrival, arrival.map(at), "{prompt}");
+      assert_eq!(intent.confidence, confidence, "{prompt}");
+      match warning {
+        Some(warning) => assert!(
+          intent.warnings.iter().any(|message| message.contains(warning)),
+          "{prompt}: {:?}",
+          intent.warnings
+        ),
+        None if confidence == High => assert!(intent.warnings.is_empty(), "{prompt}: {:?}", intent.warnings),
+        None => {}
+      }
+    }
+  }
+
+  #[test]
+  fn past_times_without_a_day_roll_to_tomorrow() {
+    let intent = parse("Luna Red at 9am", now());
+    assert_eq!(intent.arrival, Some(at("2026-02-21 09:00")));
+    assert_eq!(intent.confidence, TripParseConfidence::Medium);
+  }
+}
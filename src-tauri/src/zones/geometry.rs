@@ -0,0 +1,273 @@
+//! Planar point-in-polygon and nearest-point helpers, ported from `lib/paybyphone-zones.ts` so the
+//! desktop build returns the same answers as the Next.js routes.
+
+use geographiclib_rs::{Geodesic, InverseGeodesic};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// `[lng, lat]`, as in GeoJSON.
+pub type Position = [f64; 2];
+pub type Ring = Vec<Position>;
+pub type Polygon = Vec<Ring>;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", content = "coordinates")]
+pub enum Geometry {
+  Polygon(Polygon),
+  MultiPolygon(Vec<Polygon>),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bounds {
+  pub min_lat: f64,
+  pub max_lat: f64,
+  pub min_lng: f64,
+  pub max_lng: f64,
+}
+
+impl Geometry {
+  pub fn polygons(&self) -> &[Polygon] {
+    match self {
+      Geometry::Polygon(polygon) => std::slice::from_ref(polygon),
+      Geometry::MultiPolygon(polygons) => polygons,
+    }
+  }
+
+  pub fn contains(&self, lng: f64, lat: f64) -> bool {
+    self.polygons().iter().any(|polygon| point_in_polygon(lng, lat, polygon))
+  }
+
+  /// Closest point of the geometry to `(lng, lat)`; the point itself when it is inside.
+  pub fn nearest_point(&self, lng: f64, lat: f64) -> Position {
+    let mut best_point = [lng, lat];
+    let mut best_distance = f64::INFINITY;
+    for polygon in self.polygons() {
+      let candidate = nearest_point_on_polygon(lng, lat, polygon);
+      let distance = planar_distance_squared(lat, lng, candidate[1], candidate[0]);
+      if distance < best_distance {
+        best_distance = distance;
+        best_point = candidate;
+      }
+    }
+    best_point
+  }
+
+  pub fn bounds(&self) -> Bounds {
+    let mut bounds = Bounds::empty();
+    for position in self.polygons().iter().flatten().flatten() {
+      bounds.extend(*position);
+    }
+    bounds
+  }
+}
+
+impl Bounds {
+  pub fn empty() -> Self {
+    Bounds {
+      min_lat: f64::INFINITY,
+      max_lat: f64::NEG_INFINITY,
+      min_lng: f64::INFINITY,
+      max_lng: f64::NEG_INFINITY,
+    }
+  }
+
+  pub fn extend(&mut self, [lng, lat]: Position) {
+    self.min_lat = self.min_lat.min(lat);
+    self.max_lat = self.max_lat.max(lat);
+    self.min_lng = self.min_lng.min(lng);
+    self.max_lng = self.max_lng.max(lng);
+  }
+
+  /// `(lat, lng)` of the bounding-box center.
+  pub fn center(&self) -> (f64, f64) {
+    ((self.min_lat + self.max_lat) / 2.0, (self.min_lng + self.max_lng) / 2.0)
+  }
+}
+
+fn is_point_on_segment(lng: f64, lat: f64, [lng1, lat1]: Position, [lng2, lat2]: Position) -> bool {
+  let epsilon = 1e-10;
+  let cross = (lat - lat1) * (lng2 - lng1) - (lng - lng1) * (lat2 - lat1);
+  if cross.abs() > epsilon {
+    return false;
+  }
+
+  let dot = (lng - lng1) * (lng - lng2) + (lat - lat1) * (lat - lat2);
+  dot <= epsilon
+}
+
+/// Even-odd ray casting; points on an edge count as inside.
+fn point_in_ring(lng: f64, lat: f64, ring: &[Position]) -> bool {
+  let mut inside = false;
+  let mut j = ring.len().wrapping_sub(1);
+  for i in 0..ring.len() {
+    let [lng_i, lat_i] = ring[i];
+    let [lng_j, lat_j] = ring[j];
+
+    if is_point_on_segment(lng, lat, ring[j], ring[i]) {
+      return true;
+    }
+
+    let intersects = (lat_i > lat) != (lat_j > lat) && lng < (lng_j - lng_i) * (lat - lat_i) / (lat_j - lat_i) + lng_i;
+    if intersects {
+      inside = !inside;
+    }
+    j = i;
+  }
+  inside
+}
+
+fn point_in_polygon(lng: f64, lat: f64, polygon: &[Ring]) -> bool {
+  let Some((outer_ring, holes)) = polygon.split_first() else {
+    return false;
+  };
+  if !point_in_ring(lng, lat, outer_ring) {
+    return false;
+  }
+  !holes.iter().any(|hole| point_in_ring(lng, lat, hole))
+}
+
+fn planar_distance_squared(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+  let scale_lng = ((lat1 + lat2) / 2.0).to_radians().cos();
+  let d_lat = lat2 - lat1;
+  let d_lng = (lng2 - lng1) * scale_lng;
+  d_lat * d_lat + d_lng * d_lng
+}
+
+fn nearest_point_on_segment(lng: f64, lat: f64, [lng1, lat1]: Position, [lng2, lat2]: Position) -> Position {
+  let scale_lng = lat.to_radians().cos();
+  let (px, py) = (lng * scale_lng, lat);
+  let (x1, y1) = (lng1 * scale_lng, lat1);
+  let (x2, y2) = (lng2 * scale_lng, lat2);
+
+  let dx = x2 - x1;
+  let dy = y2 - y1;
+  let length_squared = dx * dx + dy * dy;
+  if length_squared <= 1e-16 {
+    return [lng1, lat1];
+  }
+
+  let t = (((px - x1) * dx + (py - y1) * dy) / length_squared).clamp(0.0, 1.0);
+  [(x1 + t * dx) / scale_lng, y1 + t * dy]
+}
+
+fn nearest_point_on_ring(lng: f64, lat: f64, ring: &[Position]) -> Position {
+  match ring.len() {
+    0 => return [lng, lat],
+    1 => return ring[0],
+    _ => {}
+  }
+
+  let mut best_point = ring[0];
+  let mut best_distance = f64::INFINITY;
+  let mut j = ring.len() - 1;
+  for i in 0..ring.len() {
+    let candidate = nearest_point_on_segment(lng, lat, ring[j], ring[i]);
+    let distance = planar_distance_squared(lat, lng, candidate[1], candidate[0]);
+    if distance < best_distance {
+      best_distance = distance;
+      best_point = candidate;
+    }
+    j = i;
+  }
+  best_point
+}
+
+fn nearest_point_on_polygon(lng: f64, lat: f64, polygon: &[Ring]) -> Position {
+  if point_in_polygon(lng, lat, polygon) {
+    return [lng, lat];
+  }
+
+  let mut best_point = [lng, lat];
+  let mut best_distance = f64::INFINITY;
+  for ring in polygon {
+    let candidate = nearest_point_on_ring(lng, lat, ring);
+    let distance = planar_distance_squared(lat, lng, candidate[1], candidate[0]);
+    if distance < best_distance {
+      best_distance = distance;
+      best_point = candidate;
+    }
+  }
+  best_point
+}
+
+/// Distance on the WGS84 ellipsoid.
+pub fn geodesic_meters(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+  static WGS84: OnceLock<Geodesic> = OnceLock::new();
+  WGS84.get_or_init(Geodesic::wgs84).inverse(lat1, lng1, lat2, lng2)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A 0.002° square near Mission Plaza with a 0.001° hole in the middle.
+  fn square_with_hole() -> Geometry {
+    Geometry::Polygon(vec![
+      vec![[-120.666, 35.279], [-120.664, 35.279], [-120.664, 35.281], [-120.666, 35.281], [-120.666, 35.279]],
+      vec![
+        [-120.6655, 35.2795],
+        [-120.6645, 35.2795],
+        [-120.6645, 35.2805],
+        [-120.6655, 35.2805],
+        [-120.6655, 35.2795],
+      ],
+    ])
+  }
+
+  #[test]
+  fn contains_counts_outer_edges_as_inside_and_hole_edges_as_outside() {
+    let geometry = square_with_hole();
+    for (lng, lat, inside) in [
+      (-120.6658, 35.2792, true),
+      (-120.665, 35.28, false),
+      (-120.666, 35.2795, true),
+      (-120.6655, 35.28, false),
+      (-120.6661, 35.2795, false),
+      (-120.665, 35.2811, false),
+    ] {
+      assert_eq!(geometry.contains(lng, lat), inside, "{lng},{lat}");
+    }
+  }
+
+  #[test]
+  fn multi_polygon_contains_either_part() {
+    let Geometry::Polygon(polygon) = square_with_hole() else { unreachable!() };
+    let mut shifted = polygon.clone();
+    for position in shifted.iter_mut().flatten() {
+      position[0] += 0.01;
+    }
+    let geometry = Geometry::MultiPolygon(vec![polygon, shifted]);
+
+    assert!(geometry.contains(-120.6658, 35.2792));
+    assert!(geometry.contains(-120.6558, 35.2792));
+    assert!(!geometry.contains(-120.6608, 35.2792));
+  }
+
+  #[test]
+  fn nearest_point_is_the_query_inside_and_on_the_edge_outside() {
+    let geometry = square_with_hole();
+
+    assert_eq!(geometry.nearest_point(-120.6658, 35.2792), [-120.6658, 35.2792]);
+    let [lng, lat] = geometry.nearest_point(-120.667, 35.2795);
+    assert!((lng - -120.666).abs() < 1e-9 && (lat - 35.2795).abs() < 1e-9, "{lng},{lat}");
+    let [lng, lat] = geometry.nearest_point(-120.665, 35.2815);
+    assert!((lng - -120.665).abs() < 1e-9 && (lat - 35.281).abs() < 1e-9, "{lng},{lat}");
+  }
+
+  #[test]
+  fn bounds_and_center() {
+    let bounds = square_with_hole().bounds();
+    assert_eq!((bounds.min_lng, bounds.max_lng), (-120.666, -120.664));
+    assert_eq!((bounds.min_lat, bounds.max_lat), (35.279, 35.281));
+    let (lat, lng) = bounds.center();
+    assert!((lat - 35.28).abs() < 1e-12 && (lng - -120.665).abs() < 1e-12);
+  }
+
+  #[test]
+  fn geodesic_distance_downtown() {
+    // One thousandth of a degree of latitude is about 111 m here.
+    let meters = geodesic_meters(35.279, -120.665, 35.280, -120.665);
+    assert!((110.0..112.0).contains(&meters), "{meters}");
+    assert_eq!(geodesic_meters(35.279, -120.665, 35.279, -120.665), 0.0);
+  }
+}
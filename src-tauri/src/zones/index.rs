@@ -0,0 +1,247 @@
+//! R-trees over zone polygons so a lookup touches only the zones near the query point instead of
+//! scanning every feature.
+
+use super::geometry::{geodesic_meters, Geometry, Position};
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
+
+/// Zone bounding box in `[lng, lat]`, tagged with the zone's position in the dataset.
+type BoundsEntry = GeomWithData<Rectangle<[f64; 2]>, usize>;
+/// Bounding-box center in `[lng, lat]`, tagged with the zone's position in the dataset.
+type CenterEntry = GeomWithData<[f64; 2], usize>;
+
+/// Meters per degree at the equator; the real value only grows away from it (latitude) or shrinks
+/// with `cos(lat)` (longitude), so these keep the search box from clipping a candidate.
+const MIN_METERS_PER_DEGREE_LAT: f64 = 110_574.0;
+const METERS_PER_DEGREE_LNG_AT_EQUATOR: f64 = 111_319.0;
+const SEARCH_BOX_MARGIN: f64 = 1.01;
+
+pub trait Zone {
+  fn geometry(&self) -> &Geometry;
+  /// `(lat, lng)` of the bounding-box center, which the nearest-zone fallback measures to.
+  fn center(&self) -> (f64, f64);
+}
+
+/// A zone near a query point, with the closest point of its polygon (`[lng, lat]`).
+pub struct Nearby<'a, Z> {
+  pub zone: &'a Z,
+  pub point: Position,
+  pub distance_meters: f64,
+}
+
+/// One dataset's zones, indexed by polygon bounding box for containment and by center for the
+/// nearest-zone fallback.
+pub struct ZoneLayer<Z> {
+  zones: Vec<Z>,
+  bounds: RTree<BoundsEntry>,
+  centers: RTree<CenterEntry>,
+}
+
+impl<Z: Zone> ZoneLayer<Z> {
+  pub fn new(zones: Vec<Z>) -> Self {
+    let bounds = zones
+      .iter()
+      .enumerate()
+      .map(|(position, zone)| {
+        let bounds = zone.geometry().bounds();
+        let rectangle = Rectangle::from_corners([bounds.min_lng, bounds.min_lat], [bounds.max_lng, bounds.max_lat]);
+        GeomWithData::new(rectangle, position)
+      })
+      .collect();
+    let centers = zones
+      .iter()
+      .enumerate()
+      .map(|(position, zone)| {
+        let (lat, lng) = zone.center();
+        GeomWithData::new([lng, lat], position)
+      })
+      .collect();
+
+    ZoneLayer {
+      zones,
+      bounds: RTree::bulk_load(bounds),
+      centers: RTree::bulk_load(centers),
+    }
+  }
+
+  pub fn zones(&self) -> &[Z] {
+    &self.zones
+  }
+
+  /// First zone, in dataset order, whose polygon contains the point.
+  pub fn containing(&self, lat: f64, lng: f64) -> Option<&Z> {
+    self
+      .bounds
+      .locate_all_at_point(&[lng, lat])
+      .map(|entry| entry.data)
+      .filter(|position| self.zones[*position].geometry().contains(lng, lat))
+      .min()
+      .map(|position| &self.zones[position])
+  }
+
+  /// Zone whose center is geodesically closest to the point, if it is within `max_meters`.
+  pub fn nearest_center(&self, lat: f64, lng: f64, max_meters: f64) -> Option<(&Z, f64)> {
+    self
+      .centers
+      .locate_in_envelope(&search_envelope(lat, lng, max_meters))
+      .map(|entry| {
+        let [center_lng, center_lat] = *entry.geom();
+        (entry.data, geodesic_meters(lat, lng, center_lat, center_lng))
+      })
+      .filter(|(_, distance)| *distance <= max_meters)
+      .min_by(|(a_position, a), (b_position, b)| a.total_cmp(b).then(a_position.cmp(b_position)))
+      .map(|(position, distance)| (&self.zones[position], distance))
+  }
+
+  /// Zones whose polygon comes within `max_meters` of the point (any distance when `None`), closest
+  /// first and in dataset order on ties.
+  pub fn nearby(&self, lat: f64, lng: f64, max_meters: Option<f64>) -> Vec<Nearby<'_, Z>> {
+    let mut positions: Vec<usize> = match max_meters {
+      Some(max_meters) => self
+        .bounds
+        .locate_in_envelope_intersecting(&search_envelope(lat, lng, max_meters))
+        .map(|entry| entry.data)
+        .collect(),
+      None => (0..self.zones.len()).collect(),
+    };
+    positions.sort_unstable();
+
+    let mut nearby: Vec<Nearby<'_, Z>> = positions
+      .into_iter()
+      .map(|position| {
+        let zone = &self.zones[position];
+        let point = zone.geometry().nearest_point(lng, lat);
+        Nearby {
+          zone,
+          point,
+          distance_meters: geodesic_meters(lat, lng, point[1], point[0]),
+        }
+      })
+      .filter(|candidate| max_meters.map_or(true, |max_meters| candidate.distance_meters <= max_meters))
+      .collect();
+    nearby.sort_by(|a, b| a.distance_meters.total_cmp(&b.distance_meters));
+    nearby
+  }
+}
+
+/// `[lng, lat]` box guaranteed to hold every point within `radius_meters` of the query.
+fn search_envelope(lat: f64, lng: f64, radius_meters: f64) -> AABB<[f64; 2]> {
+  let lat_delta = radius_meters * SEARCH_BOX_MARGIN / MIN_METERS_PER_DEGREE_LAT;
+  let max_lat = (lat.abs() + lat_delta).min(90.0);
+  let meters_per_degree_lng = METERS_PER_DEGREE_LNG_AT_EQUATOR * max_lat.to_radians().cos();
+  let lng_delta = if meters_per_degree_lng > 1.0 {
+    (radius_meters * SEARCH_BOX_MARGIN / meters_per_degree_lng).min(180.0)
+  } else {
+    180.0
+  };
+
+  AABB::from_corners([lng - lng_delta, lat - lat_delta], [lng + lng_delta, lat + lat_delta])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::zones::ZoneIndex;
+  use geographiclib_rs::{DirectGeodesic, Geodesic};
+  use rstar::Envelope;
+
+  /// Every 0.0005° (about 50 m) over downtown San Luis Obispo and a little beyond.
+  fn downtown_grid() -> impl Iterator<Item = (f64, f64)> {
+    (0..=28).flat_map(|row| {
+      (0..=32).map(move |column| (35.274 + row as f64 * 0.0005, -120.672 + column as f64 * 0.0005))
+    })
+  }
+
+  fn assert_matches_linear_scan<Z: Zone>(layer: &ZoneLayer<Z>) {
+    let zones = layer.zones();
+    let position_of = |zone: &Z| zones.iter().position(|candidate| std::ptr::eq(candidate, zone));
+
+    for (lat, lng) in downtown_grid() {
+      let expected = zones.iter().position(|zone| zone.geometry().contains(lng, lat));
+      assert_eq!(layer.containing(lat, lng).and_then(position_of), expected, "containing {lat},{lng}");
+
+      for max_meters in [0.0, 25.0, 100.0, 1_000.0] {
+        let expected = zones
+          .iter()
+          .enumerate()
+          .map(|(position, zone)| {
+            let (center_lat, center_lng) = zone.center();
+            (position, geodesic_meters(lat, lng, center_lat, center_lng))
+          })
+          .filter(|(_, distance)| *distance <= max_meters)
+          .min_by(|(a_position, a), (b_position, b)| a.total_cmp(b).then(a_position.cmp(b_position)));
+        let actual = layer
+          .nearest_center(lat, lng, max_meters)
+          .map(|(zone, distance)| (position_of(zone).unwrap(), distance));
+        assert_eq!(actual, expected, "nearest_center {lat},{lng} within {max_meters}");
+      }
+
+      let all = layer.nearby(lat, lng, None);
+      assert_eq!(all.len(), zones.len());
+      for max_meters in [25.0, 100.0, 1_000.0] {
+        let expected: Vec<usize> = all
+          .iter()
+          .filter(|candidate| candidate.distance_meters <= max_meters)
+          .map(|candidate| position_of(candidate.zone).unwrap())
+          .collect();
+        let actual: Vec<usize> = layer
+          .nearby(lat, lng, Some(max_meters))
+          .iter()
+          .map(|candidate| position_of(candidate.zone).unwrap())
+          .collect();
+        assert_eq!(actual, expected, "nearby {lat},{lng} within {max_meters}");
+      }
+    }
+  }
+
+  #[test]
+  fn indexed_lookups_match_a_linear_scan() {
+    let index = ZoneIndex::load_bundled().expect("bundled zones parse");
+    assert_matches_linear_scan(&index.paid);
+    assert_matches_linear_scan(&index.residential);
+  }
+
+  #[test]
+  fn search_envelope_holds_every_point_at_the_radius() {
+    let wgs84 = Geodesic::wgs84();
+    let origins = [(35.28, -120.66), (0.0, 0.0), (-33.9, 151.2), (64.8, -147.7), (89.9, 10.0), (-89.9, -10.0)];
+
+    for (lat, lng) in origins {
+      for radius in [1.0, 100.0, 5_000.0] {
+        let envelope = search_envelope(lat, lng, radius);
+        for azimuth in (0..360).step_by(15) {
+          let (point_lat, point_lng): (f64, f64) = wgs84.direct(lat, lng, azimuth as f64, radius);
+          // Near the poles `direct` may wrap longitude; the box is in unwrapped degrees.
+          let point_lng = lng + (point_lng - lng + 540.0).rem_euclid(360.0) - 180.0;
+          assert!(
+            envelope.contains_point(&[point_lng, point_lat]),
+            "{radius} m at {azimuth}° from {lat},{lng} is outside the search box"
+          );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn search_envelope_spans_all_longitudes_at_the_pole() {
+    let envelope = search_envelope(90.0, 0.0, 100.0);
+    assert_eq!(envelope.lower()[0], -180.0);
+    assert_eq!(envelope.upper()[0], 180.0);
+  }
+
+  /// The request budget is 1 ms per location fix. Averaged over the grid so one slow call on a
+  /// busy machine does not fail the test; unoptimized builds still have plenty of headroom.
+  #[test]
+  fn current_zone_lookup_takes_under_a_millisecond() {
+    let index = ZoneIndex::load_bundled().expect("bundled zones parse");
+    let points: Vec<(f64, f64)> = downtown_grid().collect();
+
+    let started = std::time::Instant::now();
+    for (lat, lng) in &points {
+      std::hint::black_box(crate::zones::resolve_current_zone(&index, *lat, *lng));
+    }
+    let per_lookup = started.elapsed() / points.len() as u32;
+
+    assert!(per_lookup < std::time::Duration::from_millis(1), "{per_lookup:?} per lookup");
+  }
+}
@@ -0,0 +1,557 @@
+mod geometry;
+mod index;
+mod recommend;
+
+use crate::calendar::{EnforcementCalendar, TIME_ZONE};
+use crate::diagnostics::LookupHistory;
+use crate::error::{CommandError, CommandResult};
+use crate::payment_profile::FieldError;
+use crate::rules::{MeterSchedule, Tariff};
+use geometry::Geometry;
+use chrono::DateTime;
+use index::{Zone, ZoneLayer};
+use serde::{Deserialize, Serialize};
+
+const DOWNTOWN_RATES_GEOJSON: &str = include_str!("../../../data/slo-downtown-parking-rates.json");
+const STREET_PARKING_GEOJSON: &str = include_str!("../../../data/slo-street-parking.json");
+const PROVISIONAL_RULES_JSON: &str = include_str!("../../../data/paybyphone-provisional-rules.json");
+const METER_SCHEDULES_JSON: &str = include_str!("../../../data/slo-meter-schedules.json");
+
+/// Bundled data files by name, for the diagnostics bundle.
+pub const BUNDLED_DATASETS: [(&str, &str); 4] = [
+  ("slo-downtown-parking-rates.json", DOWNTOWN_RATES_GEOJSON),
+  ("slo-street-parking.json", STREET_PARKING_GEOJSON),
+  ("paybyphone-provisional-rules.json", PROVISIONAL_RULES_JSON),
+  ("slo-meter-schedules.json", METER_SCHEDULES_JSON),
+];
+
+/// Same radius as `NEAREST_FALLBACK_METERS` in `/api/parking/current-zone`.
+const NEAREST_FALLBACK_METERS: f64 = 100.0;
+const POOR_GPS_WARNING_METERS: f64 = 100.0;
+
+#[derive(Deserialize)]
+struct FeatureCollection<P> {
+  features: Vec<Feature<P>>,
+}
+
+#[derive(Deserialize)]
+struct Feature<P> {
+  geometry: Option<Geometry>,
+  properties: P,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DowntownProperties {
+  meter_zone: Option<String>,
+  #[serde(rename = "Type")]
+  zone_type: Option<String>,
+  name: Option<String>,
+  label: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreetProperties {
+  #[serde(rename = "zoneID")]
+  zone_id: Option<String>,
+  code: Option<String>,
+  description: Option<String>,
+  #[serde(rename = "DISTRICT")]
+  district: Option<String>,
+  #[serde(rename = "HOURS")]
+  hours: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProvisionalRule {
+  #[serde(rename = "type")]
+  zone_type: Option<String>,
+  meter_zone: String,
+  pay_by_phone_zone: String,
+  description: String,
+}
+
+/// A downtown meter polygon and the PayByPhone zone it provisionally maps to.
+pub struct PaidZone {
+  pub meter_zone: String,
+  pub zone_type: String,
+  /// Lot number or garage name, for lots and garages.
+  pub name: Option<String>,
+  /// `None` when the rate or schedule could not be parsed.
+  pub tariff: Option<Tariff>,
+  pub geometry: Geometry,
+  pub center_lat: f64,
+  pub center_lng: f64,
+  pub pay_by_phone_zone: Option<String>,
+  pub provisional_reason: Option<String>,
+}
+
+/// A residential permit district polygon.
+pub struct ResidentialZone {
+  pub zone_id: String,
+  pub description: String,
+  pub district: String,
+  pub hours: String,
+  pub tariff: Option<Tariff>,
+  pub geometry: Geometry,
+  pub center_lat: f64,
+  pub center_lng: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchType {
+  Inside,
+  Nearest,
+  None,
+}
+
+/// Result of a single-dataset lookup: the containing zone, else the zone whose center is within the
+/// fallback radius.
+pub struct Lookup<'a, Z> {
+  pub match_type: MatchType,
+  pub distance_meters: Option<f64>,
+  pub zone: Option<&'a Z>,
+}
+
+/// Bundled zone datasets, parsed and indexed once at startup and shared through Tauri state.
+pub struct ZoneIndex {
+  paid: ZoneLayer<PaidZone>,
+  residential: ZoneLayer<ResidentialZone>,
+}
+
+impl Zone for PaidZone {
+  fn geometry(&self) -> &Geometry {
+    &self.geometry
+  }
+
+  fn center(&self) -> (f64, f64) {
+    (self.center_lat, self.center_lng)
+  }
+}
+
+impl Zone for ResidentialZone {
+  fn geometry(&self) -> &Geometry {
+    &self.geometry
+  }
+
+  fn center(&self) -> (f64, f64) {
+    (self.center_lat, self.center_lng)
+  }
+}
+
+impl ZoneIndex {
+  pub fn load_bundled() -> Result<Self, String> {
+    let rules = serde_json::from_str::<Vec<ProvisionalRule>>(PROVISIONAL_RULES_JSON)
+      .map_err(|error| format!("Failed to parse bundled PayByPhone rules: {error}"))?;
+    let downtown = serde_json::from_str::<FeatureCollection<DowntownProperties>>(DOWNTOWN_RATES_GEOJSON)
+      .map_err(|error| format!("Failed to parse bundled downtown rates GeoJSON: {error}"))?;
+    let street = serde_json::from_str::<FeatureCollection<StreetProperties>>(STREET_PARKING_GEOJSON)
+      .map_err(|error| format!("Failed to parse bundled street parking GeoJSON: {error}"))?;
+    let schedules = serde_json::from_str::<Vec<MeterSchedule>>(METER_SCHEDULES_JSON)
+      .map_err(|error| format!("Failed to parse bundled meter schedules: {error}"))?;
+
+    let paid = downtown
+      .features
+      .into_iter()
+      .filter_map(|feature| {
+        let geometry = feature.geometry?;
+        let properties = feature.properties;
+        let zone_type = properties.zone_type.unwrap_or_else(|| "Unknown".to_string());
+        let meter_zone = properties.meter_zone.unwrap_or_else(|| "Unknown".to_string());
+        let rule = rules.iter().find(|rule| {
+          rule.zone_type.as_ref().map_or(true, |rule_type| *rule_type == zone_type) && rule.meter_zone == meter_zone
+        });
+        let tariff = schedules
+          .iter()
+          .find(|schedule| schedule.zone_type == zone_type)
+          .ok_or_else(|| format!("No meter schedule for {zone_type} zones"))
+          .and_then(|schedule| Tariff::metered(&meter_zone, properties.label.as_deref().unwrap_or_default(), schedule));
+        let (center_lat, center_lng) = geometry.bounds().center();
+
+        Some(PaidZone {
+          pay_by_phone_zone: rule.map(|rule| rule.pay_by_phone_zone.clone()),
+          provisional_reason: rule.map(|rule| rule.description.clone()),
+          tariff: tariff
+            .inspect_err(|error| log::warn!("No tariff for {meter_zone} {zone_type} zone: {error}"))
+            .ok(),
+          name: properties.name.filter(|name| !name.trim().is_empty()),
+          zone_type,
+          meter_zone,
+          geometry,
+          center_lat,
+          center_lng,
+        })
+      })
+      .collect();
+
+    let residential = street
+      .features
+      .into_iter()
+      .filter_map(|feature| {
+        let geometry = feature.geometry?;
+        let properties = feature.properties;
+        let zone_id = properties
+          .zone_id
+          .or(properties.code)
+          .unwrap_or_else(|| "UNKNOWN".to_string());
+        let hours = properties.hours.unwrap_or_default();
+        let tariff = Tariff::residential(&hours)
+          .inspect_err(|error| log::warn!("No tariff for residential zone {zone_id}: {error}"))
+          .ok();
+        let (center_lat, center_lng) = geometry.bounds().center();
+
+        Some(ResidentialZone {
+          description: properties.description.unwrap_or_else(|| zone_id.clone()),
+          district: properties.district.unwrap_or_default(),
+          hours,
+          tariff,
+          zone_id,
+          geometry,
+          center_lat,
+          center_lng,
+        })
+      })
+      .collect();
+
+    Ok(ZoneIndex {
+      paid: ZoneLayer::new(paid),
+      residential: ZoneLayer::new(residential),
+    })
+  }
+
+  pub fn lookup_paid(&self, lat: f64, lng: f64, nearest_fallback_meters: f64) -> Lookup<'_, PaidZone> {
+    lookup(&self.paid, lat, lng, nearest_fallback_meters)
+  }
+
+  pub fn lookup_residential(&self, lat: f64, lng: f64, nearest_fallback_meters: f64) -> Lookup<'_, ResidentialZone> {
+    lookup(&self.residential, lat, lng, nearest_fallback_meters)
+  }
+
+  /// Every meter polygon billed under PayByPhone zone `zone_number`, in dataset order.
+  pub fn paid_zones_numbered<'a>(&'a self, zone_number: &'a str) -> impl Iterator<Item = &'a PaidZone> + 'a {
+    self
+      .paid
+      .zones()
+      .iter()
+      .filter(move |zone| zone.pay_by_phone_zone.as_deref() == Some(zone_number))
+  }
+
+  pub fn residential_zone(&self, zone_id: &str) -> Option<&ResidentialZone> {
+    self
+      .residential
+      .zones()
+      .iter()
+      .find(|zone| zone.zone_id.eq_ignore_ascii_case(zone_id))
+  }
+}
+
+fn lookup<Z: Zone>(layer: &ZoneLayer<Z>, lat: f64, lng: f64, nearest_fallback_meters: f64) -> Lookup<'_, Z> {
+  if let Some(containing) = layer.containing(lat, lng) {
+    return Lookup {
+      match_type: MatchType::Inside,
+      distance_meters: Some(0.0),
+      zone: Some(containing),
+    };
+  }
+
+  match layer.nearest_center(lat, lng, nearest_fallback_meters) {
+    Some((zone, distance)) => Lookup {
+      match_type: MatchType::Nearest,
+      distance_meters: Some(distance),
+      zone: Some(zone),
+    },
+    None => Lookup {
+      match_type: MatchType::None,
+      distance_meters: None,
+      zone: None,
+    },
+  }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ZoneCategory {
+  Paid,
+  Residential,
+  None,
+}
+
+impl ZoneCategory {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ZoneCategory::Paid => "paid",
+      ZoneCategory::Residential => "residential",
+      ZoneCategory::None => "none",
+    }
+  }
+}
+
+/// Same shape as the `zone` object returned by `/api/parking/current-zone`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentZone {
+  pub category: ZoneCategory,
+  pub match_type: MatchType,
+  pub distance_meters: Option<f64>,
+  pub zone_number: Option<String>,
+  pub rate: Option<String>,
+  pub payment_eligible: bool,
+  pub payment_entry_label: String,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LookupLocation {
+  pub lat: f64,
+  pub lng: f64,
+  pub accuracy_meters: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentZoneResponse {
+  pub location: LookupLocation,
+  pub zone: CurrentZone,
+  pub snapshot_at: String,
+  pub warnings: Vec<String>,
+}
+
+fn distance_label(match_type: MatchType, distance_meters: Option<f64>) -> String {
+  match (match_type, distance_meters) {
+    (MatchType::Nearest, Some(distance)) => format!(" (~{}m away)", distance.round()),
+    _ => String::new(),
+  }
+}
+
+/// Paid zone first, then residential permit district, mirroring the API route.
+pub fn resolve_current_zone(index: &ZoneIndex, lat: f64, lng: f64) -> CurrentZone {
+  let paid = index.lookup_paid(lat, lng, NEAREST_FALLBACK_METERS);
+  if let Some((zone, zone_number)) = paid
+    .zone
+    .and_then(|zone| zone.pay_by_phone_zone.as_ref().map(|number| (zone, number)))
+  {
+    return CurrentZone {
+      category: ZoneCategory::Paid,
+      match_type: paid.match_type,
+      distance_meters: paid.distance_meters,
+      zone_number: Some(zone_number.clone()),
+      rate: Some(zone.meter_zone.clone()),
+      payment_eligible: true,
+      payment_entry_label: "Proceed to Payment".to_string(),
+      message: format!(
+        "Paid Zone {zone_number} at {}{}",
+        zone.meter_zone,
+        distance_label(paid.match_type, paid.distance_meters)
+      ),
+    };
+  }
+
+  let residential = index.lookup_residential(lat, lng, NEAREST_FALLBACK_METERS);
+  if let Some(zone) = residential.zone {
+    return CurrentZone {
+      category: ZoneCategory::Residential,
+      match_type: residential.match_type,
+      distance_meters: residential.distance_meters,
+      zone_number: Some(zone.zone_id.clone()),
+      rate: Some("Permit required".to_string()),
+      payment_eligible: false,
+      payment_entry_label: "Residential permit area".to_string(),
+      message: format!(
+        "Residential Zone {} (Permit required){}",
+        zone.zone_id,
+        distance_label(residential.match_type, residential.distance_meters)
+      ),
+    };
+  }
+
+  CurrentZone {
+    category: ZoneCategory::None,
+    match_type: MatchType::None,
+    distance_meters: None,
+    zone_number: None,
+    rate: None,
+    payment_eligible: false,
+    payment_entry_label: "No payment available".to_string(),
+    message: "No nearby paid or residential zone found within 100m. Move closer to marked parking streets/blocks."
+      .to_string(),
+  }
+}
+
+pub fn validate_coordinate(lat: f64, lng: f64) -> CommandResult<()> {
+  let mut fields = Vec::new();
+  if !lat.is_finite() || !(-90.0..=90.0).contains(&lat) {
+    fields.push(FieldError {
+      field: "lat".to_string(),
+      message: "Latitude must be between -90 and 90.".to_string(),
+    });
+  }
+  if !lng.is_finite() || !(-180.0..=180.0).contains(&lng) {
+    fields.push(FieldError {
+      field: "lng".to_string(),
+      message: "Longitude must be between -180 and 180.".to_string(),
+    });
+  }
+
+  if fields.is_empty() {
+    Ok(())
+  } else {
+    Err(CommandError::Validation { fields })
+  }
+}
+
+/// Offline equivalent of `POST /api/parking/current-zone`.
+#[tauri::command]
+pub fn lookup_current_zone(
+  zones: tauri::State<'_, ZoneIndex>,
+  history: tauri::State<'_, LookupHistory>,
+  lat: f64,
+  lng: f64,
+  accuracy_meters: Option<f64>,
+) -> CommandResult<CurrentZoneResponse> {
+  validate_coordinate(lat, lng)?;
+  let accuracy_meters = accuracy_meters.filter(|accuracy| accuracy.is_finite() && *accuracy >= 0.0);
+
+  let mut warnings = Vec::new();
+  if let Some(accuracy) = accuracy_meters.filter(|accuracy| *accuracy > POOR_GPS_WARNING_METERS) {
+    warnings.push(format!(
+      "GPS accuracy is currently low ({}m). Zone detection may be approximate.",
+      accuracy.round()
+    ));
+  }
+
+  let response = CurrentZoneResponse {
+    location: LookupLocation {
+      lat,
+      lng,
+      accuracy_meters,
+    },
+    zone: resolve_current_zone(&zones, lat, lng),
+    snapshot_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+    warnings,
+  };
+  history.record(&response);
+  Ok(response)
+}
+
+/// Offline equivalent of the ranking behind `POST /api/parking/recommend`, for a destination the
+/// frontend has already resolved to coordinates. With `arrival_time` (RFC 3339, like the trip
+/// parser's `arrivalTimeIso`) options are priced for a stay of `stay_minutes` (two hours by default)
+/// and ranked by distance and cost together.
+#[tauri::command]
+pub fn recommend_parking(
+  zones: tauri::State<'_, ZoneIndex>,
+  calendar: tauri::State<'_, EnforcementCalendar>,
+  lat: f64,
+  lng: f64,
+  limit: Option<u32>,
+  arrival_time: Option<String>,
+  stay_minutes: Option<u32>,
+) -> CommandResult<recommend::ParkingRecommendations> {
+  validate_coordinate(lat, lng)?;
+  let stay = match arrival_time.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+    Some(arrival_time) => {
+      let arrival = DateTime::parse_from_rfc3339(arrival_time).map_err(|_| CommandError::Validation {
+        fields: vec![FieldError {
+          field: "arrivalTime".to_string(),
+          message: "Arrival time must be a timestamp like 2026-02-22T20:00:00-08:00.".to_string(),
+        }],
+      })?;
+      Some(recommend::Stay {
+        arrival: arrival.with_timezone(&TIME_ZONE),
+        minutes: recommend::normalize_stay_minutes(stay_minutes)?,
+      })
+    }
+    None => None,
+  };
+  recommend::recommend(&zones, &calendar, lat, lng, recommend::normalize_limit(limit), stay.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Points in downtown San Luis Obispo, picked against the bundled datasets.
+  const DOWNTOWN_CORE: (f64, f64) = (35.2773, -120.6650);
+  const NEAR_LOT_10: (f64, f64) = (35.2786, -120.6652);
+  const INSIDE_PALM_GARAGE: (f64, f64) = (35.2817, -120.6640);
+  const NEAR_PALM_GARAGE: (f64, f64) = (35.2820, -120.6645);
+  const MISSION_ORCHARD: (f64, f64) = (35.2810, -120.6653);
+  const NEAR_MISSION_ORCHARD: (f64, f64) = (35.2822, -120.6650);
+  const OFFSHORE: (f64, f64) = (35.1000, -121.0000);
+
+  fn index() -> ZoneIndex {
+    ZoneIndex::load_bundled().expect("bundled zones parse")
+  }
+
+  #[test]
+  fn paid_lookup_matches_inside_nearest_and_none() {
+    let index = index();
+
+    let (lat, lng) = DOWNTOWN_CORE;
+    let inside = index.lookup_paid(lat, lng, NEAREST_FALLBACK_METERS);
+    assert_eq!(inside.match_type, MatchType::Inside);
+    assert_eq!(inside.distance_meters, Some(0.0));
+    let zone = inside.zone.unwrap();
+    assert_eq!((zone.zone_type.as_str(), zone.pay_by_phone_zone.as_deref()), ("Downtown Core", Some("80511")));
+
+    let (lat, lng) = NEAR_LOT_10;
+    let nearest = index.lookup_paid(lat, lng, NEAREST_FALLBACK_METERS);
+    assert_eq!(nearest.match_type, MatchType::Nearest);
+    assert!((38.0..39.0).contains(&nearest.distance_meters.unwrap()));
+    assert_eq!(nearest.zone.unwrap().name.as_deref(), Some("10"));
+
+    let none = index.lookup_paid(lat, lng, 30.0);
+    assert_eq!(none.match_type, MatchType::None);
+    assert!(none.zone.is_none() && none.distance_meters.is_none());
+  }
+
+  #[test]
+  fn residential_lookup_matches_inside_and_nearest() {
+    let index = index();
+
+    let (lat, lng) = MISSION_ORCHARD;
+    let inside = index.lookup_residential(lat, lng, NEAREST_FALLBACK_METERS);
+    assert_eq!(inside.match_type, MatchType::Inside);
+    assert_eq!(inside.zone.unwrap().zone_id, "RPD-MSSNRC");
+
+    let (lat, lng) = NEAR_MISSION_ORCHARD;
+    let nearest = index.lookup_residential(lat, lng, NEAREST_FALLBACK_METERS);
+    assert_eq!(nearest.match_type, MatchType::Nearest);
+    assert_eq!(nearest.zone.unwrap().zone_id, "RPD-MSSNRC");
+  }
+
+  #[test]
+  fn current_zone_prefers_paid_then_falls_back_to_residential() {
+    let index = index();
+    let cases = [
+      (DOWNTOWN_CORE, "paid", MatchType::Inside, Some("80511"), "Paid Zone 80511 at $2.75/hr"),
+      (NEAR_LOT_10, "paid", MatchType::Nearest, Some("80512"), "Paid Zone 80512 at $2.75/hr (~38m away)"),
+      (MISSION_ORCHARD, "residential", MatchType::Inside, Some("RPD-MSSNRC"), "Residential Zone RPD-MSSNRC"),
+      // The closest paid zone is a garage with no PayByPhone zone, so the residential district wins.
+      (NEAR_PALM_GARAGE, "residential", MatchType::Nearest, Some("RPD-MSSNRC"), "Residential Zone RPD-MSSNRC"),
+      (INSIDE_PALM_GARAGE, "none", MatchType::None, None, "No nearby paid or residential zone"),
+      (OFFSHORE, "none", MatchType::None, None, "No nearby paid or residential zone"),
+    ];
+
+    for ((lat, lng), category, match_type, zone_number, message) in cases {
+      let zone = resolve_current_zone(&index, lat, lng);
+      assert_eq!(zone.category.as_str(), category, "{lat},{lng}");
+      assert_eq!(zone.match_type, match_type, "{lat},{lng}");
+      assert_eq!(zone.zone_number.as_deref(), zone_number, "{lat},{lng}");
+      assert!(zone.message.starts_with(message), "{lat},{lng}: {}", zone.message);
+      assert_eq!(zone.payment_eligible, category == "paid", "{lat},{lng}");
+    }
+  }
+
+  #[test]
+  fn coordinates_must_be_in_range() {
+    assert!(validate_coordinate(35.28, -120.66).is_ok());
+    assert!(validate_coordinate(90.0, 180.0).is_ok());
+    for (lat, lng) in [(90.1, 0.0), (0.0, -180.1), (f64::NAN, 0.0), (0.0, f64::INFINITY)] {
+      assert!(validate_coordinate(lat, lng).is_err(), "{lat},{lng}");
+    }
+  }
+}
@@ -0,0 +1,403 @@
+//! Port of `recommendParkingForResolvedDestination` in `lib/parking-recommendation-engine.ts`.
+
+use super::{ZoneCategory, ZoneIndex};
+use crate::calendar::EnforcementCalendar;
+use crate::error::{CommandError, CommandResult};
+use crate::payment_profile::FieldError;
+use crate::rules::{self, Estimate, Tariff, Violation, ViolationKind};
+use chrono::DateTime;
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::HashSet;
+
+pub const MAX_DESTINATION_DISTANCE_METERS: f64 = 1000.0;
+pub const MAX_RESIDENTIAL_RECOMMENDATION_DISTANCE_METERS: f64 = 500.0;
+const DEFAULT_RECOMMENDATION_LIMIT: u32 = 5;
+const MAX_RECOMMENDATION_LIMIT: u32 = 5;
+pub const DEFAULT_STAY_MINUTES: u32 = 120;
+/// What 100 m of extra walking is worth when ranking by distance and price together: about a
+/// minute and a half on foot for a quarter.
+const WALKING_CENTS_PER_100_METERS: f64 = 25.0;
+
+/// When the driver arrives and how long they stay, for cost-aware ranking.
+pub struct Stay {
+  pub arrival: DateTime<Tz>,
+  pub minutes: u32,
+}
+
+impl Stay {
+  fn end(&self) -> DateTime<Tz> {
+    self.arrival + chrono::Duration::minutes(i64::from(self.minutes))
+  }
+}
+
+/// Estimated cost of one option for the planned stay.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StayCost {
+  pub total_cents: u32,
+  pub total: String,
+  /// Minutes of the stay that are paid (or, in a residential district, need a permit).
+  pub enforced_minutes: u32,
+  pub free_minutes: u32,
+  pub daily_max_applied: bool,
+  pub violations: Vec<Violation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaidRecommendation {
+  pub zone_number: String,
+  pub price: String,
+  pub distance_meters: f64,
+  pub zone_lat: f64,
+  pub zone_lng: f64,
+  pub rationale: String,
+  /// Only when an arrival time was given.
+  pub cost: Option<StayCost>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResidentialRecommendation {
+  pub zone_number: String,
+  pub price: String,
+  pub distance_meters: f64,
+  pub zone_lat: f64,
+  pub zone_lng: f64,
+  pub district: String,
+  pub hours: String,
+  pub description: String,
+  pub rationale: String,
+  /// Only when an arrival time was given.
+  pub cost: Option<StayCost>,
+}
+
+/// Same lists as `ParkingRecommendationResponse`, minus the Places fields the command does not
+/// receive.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParkingRecommendations {
+  pub destination_lat: f64,
+  pub destination_lng: f64,
+  pub nearest_parking_distance_meters: f64,
+  pub arrival_time: Option<String>,
+  pub stay_minutes: Option<u32>,
+  pub recommendations: Vec<PaidRecommendation>,
+  pub residential_recommendations: Vec<ResidentialRecommendation>,
+  pub warnings: Vec<String>,
+}
+
+pub fn normalize_limit(limit: Option<u32>) -> usize {
+  limit
+    .unwrap_or(DEFAULT_RECOMMENDATION_LIMIT)
+    .clamp(1, MAX_RECOMMENDATION_LIMIT) as usize
+}
+
+pub fn normalize_stay_minutes(stay_minutes: Option<u32>) -> CommandResult<u32> {
+  let stay_minutes = stay_minutes.unwrap_or(DEFAULT_STAY_MINUTES);
+  if (1..=rules::MAX_ESTIMATE_MINUTES).contains(&stay_minutes) {
+    Ok(stay_minutes)
+  } else {
+    Err(CommandError::Validation {
+      fields: vec![FieldError {
+        field: "stayMinutes".to_string(),
+        message: format!("Planned stay must be between 1 and {} minutes.", rules::MAX_ESTIMATE_MINUTES),
+      }],
+    })
+  }
+}
+
+/// Closest paid zones (one per PayByPhone zone number) and residential districts within walking
+/// distance of the destination. Fails when no paid zone is within
+/// `MAX_DESTINATION_DISTANCE_METERS`, like the TypeScript engine with `enforceDowntownDistance`.
+///
+/// With a `stay`, each option is priced for that window and both lists are ranked by walking
+/// distance and cost together; options that break a time limit, need a permit or are closed come
+/// last.
+pub fn recommend(
+  index: &ZoneIndex,
+  calendar: &EnforcementCalendar,
+  lat: f64,
+  lng: f64,
+  limit: usize,
+  stay: Option<&Stay>,
+) -> CommandResult<ParkingRecommendations> {
+  let mut paid_candidates = index.paid.nearby(lat, lng, Some(MAX_DESTINATION_DISTANCE_METERS));
+  if paid_candidates.iter().all(|candidate| candidate.zone.pay_by_phone_zone.is_none()) {
+    // Measure the whole dataset only to say how far away downtown is.
+    paid_candidates = index.paid.nearby(lat, lng, None);
+  }
+  paid_candidates.retain(|candidate| candidate.zone.pay_by_phone_zone.is_some());
+
+  let nearest_parking_distance_meters = paid_candidates
+    .first()
+    .map(|candidate| candidate.distance_meters)
+    .ok_or_else(|| {
+      CommandError::NotFound("No paid downtown parking zones were found for this destination.".to_string())
+    })?;
+  if nearest_parking_distance_meters > MAX_DESTINATION_DISTANCE_METERS {
+    return Err(CommandError::Validation {
+      fields: vec![FieldError {
+        field: "destination".to_string(),
+        message: format!(
+          "Destination is too far from downtown paid parking zones ({}m away). Please refine your destination or choose one closer to downtown San Luis Obispo (within {}m).",
+          nearest_parking_distance_meters.round(),
+          MAX_DESTINATION_DISTANCE_METERS
+        ),
+      }],
+    });
+  }
+
+  let mut priced_paid: Vec<_> = paid_candidates
+    .into_iter()
+    .map(|candidate| {
+      let zone_number = candidate.zone.pay_by_phone_zone.as_deref().unwrap_or_default();
+      let estimate = estimate_stay(
+        calendar,
+        zone_number,
+        ZoneCategory::Paid,
+        candidate.zone.tariff.as_ref(),
+        stay,
+      );
+      (candidate, estimate)
+    })
+    .collect();
+  sort_by_stay_cost(&mut priced_paid, stay.is_some(), |(candidate, estimate)| {
+    (candidate.distance_meters, estimate.as_ref())
+  });
+
+  let mut seen_zone_numbers = HashSet::new();
+  let recommendations: Vec<PaidRecommendation> = priced_paid
+    .into_iter()
+    .filter_map(|(candidate, estimate)| {
+      let zone_number = candidate.zone.pay_by_phone_zone.clone()?;
+      if !seen_zone_numbers.insert(zone_number.clone()) {
+        return None;
+      }
+
+      let mut rationale = format!(
+        "Paid Zone {zone_number} at {}, {}m from the destination.",
+        candidate.zone.meter_zone,
+        candidate.distance_meters.round()
+      );
+      if let Some(reason) = &candidate.zone.provisional_reason {
+        rationale.push_str(&format!(" {reason}."));
+      }
+      let cost = stay.zip(estimate).map(|(stay, estimate)| {
+        rationale.push_str(&format!(" {}", cost_summary(&estimate, stay, false)));
+        stay_cost(estimate, stay)
+      });
+
+      Some(PaidRecommendation {
+        zone_number,
+        price: candidate.zone.meter_zone.clone(),
+        distance_meters: candidate.distance_meters,
+        zone_lat: candidate.point[1],
+        zone_lng: candidate.point[0],
+        rationale,
+        cost,
+      })
+    })
+    .take(limit)
+    .collect();
+
+  let mut priced_residential: Vec<_> = index
+    .residential
+    .nearby(lat, lng, Some(MAX_RESIDENTIAL_RECOMMENDATION_DISTANCE_METERS))
+    .into_iter()
+    .map(|candidate| {
+      let estimate = estimate_stay(
+        calendar,
+        &candidate.zone.zone_id,
+        ZoneCategory::Residential,
+        candidate.zone.tariff.as_ref(),
+        stay,
+      );
+      (candidate, estimate)
+    })
+    .collect();
+  sort_by_stay_cost(&mut priced_residential, stay.is_some(), |(candidate, estimate)| {
+    (candidate.distance_meters, estimate.as_ref())
+  });
+
+  let mut seen_zone_ids = HashSet::new();
+  let residential_recommendations: Vec<ResidentialRecommendation> = priced_residential
+    .into_iter()
+    .filter(|(candidate, _)| seen_zone_ids.insert(candidate.zone.zone_id.clone()))
+    .take(limit)
+    .map(|(candidate, estimate)| {
+      let zone = candidate.zone;
+      let mut rationale = format!(
+        "{} residential permit district, {}m from the destination; permit required",
+        zone.description,
+        candidate.distance_meters.round()
+      );
+      if !zone.hours.is_empty() {
+        rationale.push_str(&format!(" {}", zone.hours));
+      }
+      rationale.push('.');
+      let cost = stay.zip(estimate).map(|(stay, estimate)| {
+        rationale.push_str(&format!(" {}", cost_summary(&estimate, stay, true)));
+        stay_cost(estimate, stay)
+      });
+
+      ResidentialRecommendation {
+        zone_number: zone.zone_id.clone(),
+        price: "Permit required".to_string(),
+        distance_meters: candidate.distance_meters,
+        zone_lat: candidate.point[1],
+        zone_lng: candidate.point[0],
+        district: zone.district.clone(),
+        hours: zone.hours.clone(),
+        description: zone.description.clone(),
+        rationale,
+        cost,
+      }
+    })
+    .collect();
+
+  let mut warnings = Vec::new();
+  if residential_recommendations.is_empty() {
+    warnings.push(format!(
+      "No residential zones found within {MAX_RESIDENTIAL_RECOMMENDATION_DISTANCE_METERS}m of the destination."
+    ));
+  }
+  if stay.is_some()
+    && recommendations
+      .iter()
+      .all(|recommendation| recommendation.cost.as_ref().map_or(true, |cost| !cost.violations.is_empty()))
+  {
+    warnings
+      .push("Every recommended paid zone has a time limit, closure or unknown rate during this stay.".to_string());
+  }
+
+  Ok(ParkingRecommendations {
+    destination_lat: lat,
+    destination_lng: lng,
+    nearest_parking_distance_meters,
+    arrival_time: stay.map(|stay| rules::timestamp(stay.arrival)),
+    stay_minutes: stay.map(|stay| stay.minutes),
+    recommendations,
+    residential_recommendations,
+    warnings,
+  })
+}
+
+fn estimate_stay(
+  calendar: &EnforcementCalendar,
+  zone: &str,
+  category: ZoneCategory,
+  tariff: Option<&Tariff>,
+  stay: Option<&Stay>,
+) -> Option<Estimate> {
+  let (stay, tariff) = stay.zip(tariff)?;
+  Some(tariff.estimate(stay.arrival, stay.end(), &calendar.for_zone(zone, category)))
+}
+
+/// Ranks options without problems first, then by price plus walking distance (see
+/// `WALKING_CENTS_PER_100_METERS`). With a stay, an option without a tariff has an unknown price and
+/// ranks with the problem ones; without a stay only distance counts. The sort is stable, so ties
+/// keep the closest-first order.
+fn sort_by_stay_cost<T>(options: &mut [T], has_stay: bool, key: impl Fn(&T) -> (f64, Option<&Estimate>)) {
+  let score = |option: &T| {
+    let (distance_meters, estimate) = key(option);
+    let has_problem = estimate.map_or(has_stay, |estimate| !estimate.violations.is_empty());
+    let cents = estimate.map_or(0.0, |estimate| f64::from(estimate.total_cents));
+    (has_problem, cents + distance_meters / 100.0 * WALKING_CENTS_PER_100_METERS)
+  };
+  options.sort_by(|a, b| {
+    let (a, b) = (score(a), score(b));
+    a.0.cmp(&b.0).then(a.1.total_cmp(&b.1))
+  });
+}
+
+fn stay_cost(estimate: Estimate, stay: &Stay) -> StayCost {
+  StayCost {
+    total_cents: estimate.total_cents,
+    total: rules::format_cents(estimate.total_cents),
+    enforced_minutes: estimate.enforced_minutes,
+    free_minutes: stay.minutes.saturating_sub(estimate.enforced_minutes),
+    daily_max_applied: estimate.daily_max_applied,
+    violations: estimate.violations,
+  }
+}
+
+/// One or two sentences for the rationale: what the stay costs and any rule it breaks.
+fn cost_summary(estimate: &Estimate, stay: &Stay, residential: bool) -> String {
+  let window = format!(
+    "{} from {}",
+    rules::format_minutes(stay.minutes),
+    rules::format_local(stay.arrival)
+  );
+  let mut summary = if residential && estimate.enforced_minutes == 0 {
+    format!("Permit hours do not overlap this stay ({window}), so parking there is free.")
+  } else if residential {
+    format!(
+      "Permit hours cover {} of this stay ({window}).",
+      rules::format_minutes(estimate.enforced_minutes)
+    )
+  } else if estimate.enforced_minutes == 0 {
+    format!("Free for this stay ({window}): meters are not enforced then.")
+  } else {
+    format!(
+      "Estimated {} for this stay ({window}; {} enforced{}).",
+      rules::format_cents(estimate.total_cents),
+      rules::format_minutes(estimate.enforced_minutes),
+      if estimate.daily_max_applied { ", daily max applied" } else { "" }
+    )
+  };
+  for violation in &estimate.violations {
+    // The permit sentence above already says it for residential districts.
+    if !(residential && violation.kind == ViolationKind::PermitRequired) {
+      summary.push_str(&format!(" {}", violation.message));
+    }
+  }
+  summary
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn estimate(total_cents: u32, violations: Vec<Violation>) -> Estimate {
+    Estimate {
+      periods: Vec::new(),
+      enforced_minutes: 60,
+      total_cents,
+      daily_max_applied: false,
+      violations,
+    }
+  }
+
+  fn sorted(options: &[(&'static str, f64, Option<Estimate>)], has_stay: bool) -> Vec<&'static str> {
+    let mut options = options.to_vec();
+    sort_by_stay_cost(&mut options, has_stay, |(_, distance_meters, estimate)| {
+      (*distance_meters, estimate.as_ref())
+    });
+    options.into_iter().map(|(name, _, _)| name).collect()
+  }
+
+  #[test]
+  fn unpriced_options_rank_with_problem_ones_during_a_stay() {
+    let over_limit = Violation {
+      kind: ViolationKind::MaxStay,
+      message: "Stay is longer than the 2 hour limit.".to_string(),
+    };
+    let options = [
+      ("unpriced", 50.0, None),
+      ("over limit", 100.0, Some(estimate(100, vec![over_limit]))),
+      ("cheap", 300.0, Some(estimate(150, Vec::new()))),
+      ("pricey", 100.0, Some(estimate(400, Vec::new()))),
+    ];
+
+    assert_eq!(sorted(&options, true), ["cheap", "pricey", "unpriced", "over limit"]);
+  }
+
+  #[test]
+  fn only_distance_counts_without_a_stay() {
+    let options = [("near", 50.0, None), ("middle", 100.0, None), ("far", 300.0, None)];
+
+    assert_eq!(sorted(&options, false), ["near", "middle", "far"]);
+  }
+}
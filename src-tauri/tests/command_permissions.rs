@@ -0,0 +1,107 @@
+//! Every command in `generate_handler!` must be listed in `build.rs`, so Tauri generates its
+//! `allow-*`/`deny-*` permissions, and granted by some capability. A command missing from
+//! `build.rs` stays reachable from every window; one missing from the capabilities from none.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+fn read(relative_path: &str) -> String {
+  let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(relative_path);
+  fs::read_to_string(&path).unwrap_or_else(|error| panic!("failed to read {}: {error}", path.display()))
+}
+
+fn between<'a>(source: &'a str, start: &str, end: &str) -> &'a str {
+  let from = source
+    .find(start)
+    .unwrap_or_else(|| panic!("missing {start:?}"))
+    + start.len();
+  let to = source[from..]
+    .find(end)
+    .unwrap_or_else(|| panic!("missing {end:?} after {start:?}"));
+  &source[from..from + to]
+}
+
+fn list_items(list: &str) -> impl Iterator<Item = &str> {
+  list.split(',').map(str::trim).filter(|item| !item.is_empty())
+}
+
+fn registered_commands() -> BTreeSet<String> {
+  let lib = read("src/lib.rs");
+  list_items(between(&lib, "generate_handler![", "]"))
+    .map(|path| path.rsplit("::").next().unwrap_or(path).to_string())
+    .collect()
+}
+
+fn manifest_commands() -> BTreeSet<String> {
+  let build = read("build.rs");
+  list_items(between(&build, "const COMMANDS: &[&str] = &[", "];"))
+    .map(|command| command.trim_matches('"').to_string())
+    .collect()
+}
+
+/// Permission identifiers granted by any file in `capabilities/`.
+fn granted_permissions() -> BTreeSet<String> {
+  let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("capabilities");
+  let mut permissions = BTreeSet::new();
+  for entry in fs::read_dir(&dir).expect("failed to list capabilities") {
+    let path = entry.expect("failed to list capabilities").path();
+    if !path.extension().is_some_and(|extension| extension == "json") {
+      continue;
+    }
+    let capability: serde_json::Value =
+      serde_json::from_str(&fs::read_to_string(&path).expect("failed to read capability")).expect("capability is JSON");
+    for permission in capability["permissions"].as_array().into_iter().flatten() {
+      let identifier = permission
+        .as_str()
+        .or_else(|| permission["identifier"].as_str())
+        .unwrap_or_else(|| panic!("unexpected permission entry in {}", path.display()));
+      permissions.insert(identifier.to_string());
+    }
+  }
+  permissions
+}
+
+fn allow_permission(command: &str) -> String {
+  format!("allow-{}", command.replace('_', "-"))
+}
+
+#[test]
+fn every_registered_command_has_generated_permissions() {
+  let registered = registered_commands();
+  let manifest = manifest_commands();
+
+  let unlisted: Vec<_> = registered.difference(&manifest).collect();
+  assert!(unlisted.is_empty(), "add these commands to COMMANDS in build.rs: {unlisted:?}");
+  let stale: Vec<_> = manifest.difference(&registered).collect();
+  assert!(stale.is_empty(), "build.rs lists commands that are not registered: {stale:?}");
+}
+
+#[test]
+fn every_registered_command_is_granted_by_a_capability() {
+  let granted = granted_permissions();
+  let ungranted: Vec<_> = registered_commands()
+    .into_iter()
+    .filter(|command| !granted.contains(&allow_permission(command)))
+    .collect();
+  assert!(
+    ungranted.is_empty(),
+    "grant these commands in a capabilities/*.json file: {ungranted:?}"
+  );
+}
+
+#[test]
+fn capabilities_only_reference_known_commands() {
+  let known: BTreeSet<String> = manifest_commands()
+    .iter()
+    .flat_map(|command| {
+      let permission = allow_permission(command);
+      [permission.replacen("allow-", "deny-", 1), permission]
+    })
+    .collect();
+  let unknown: Vec<_> = granted_permissions()
+    .into_iter()
+    .filter(|permission| !permission.contains(':') && !known.contains(permission))
+    .collect();
+  assert!(unknown.is_empty(), "capabilities grant unknown app permissions: {unknown:?}");
+}